@@ -1,12 +1,83 @@
 //! Include anomation implementation for the engine.
 //! - [`TransformAnimation`]: Aniamtion releated to an object transform.
+//! - [`Animator`]: Chainable [`Tween`] for transform and sprite colour effect.
+//!
+//! Both send an [`AnimationFinished`] event when done, and can run a [`FollowUp`] system. A
+//! [`TransformAnimation`] interrupted by [`TransformAnimation::start`] is not done, only the new
+//! animation send the event once it finish.
+//!
+//! Animations advance with the virtual [`Time`] of the frame, multiplied by the [`TimeScale`]
+//! resource, so they play at the same speed at any frame rate.
 
 use std::time::Duration;
 
+use bevy::ecs::system::SystemId;
 use bevy::prelude::*;
 
 use crate::prelude::*;
 
+mod tween;
+pub use tween::*;
+
+/// Fastest [`TimeScale`], higher scale are clamped to it.
+pub static MAX_TIME_SCALE: f32 = 100.0;
+
+/// Resource scaling the speed of every animation, `1.0` being normal speed and `0.0` pausing
+/// them. This only affect animation, not the rest of the game.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct TimeScale(pub f32);
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        TimeScale(1.0)
+    }
+}
+
+impl TimeScale {
+    /// Get the scaled time since the last frame. Negative and `NaN` scale are treated as paused,
+    /// and scale above [`MAX_TIME_SCALE`] are clamped to it.
+    #[must_use]
+    pub fn delta(self, time: &Time) -> Duration {
+        time.delta().mul_f32(self.0.max(0.0).min(MAX_TIME_SCALE))
+    }
+}
+
+/// Kind of animation that finished.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimationKind {
+    /// A [`TransformAnimation`] moving to the current [`GridTransform`].
+    Transform,
+    /// An [`Animator`] playing a [`Tweenable`].
+    Tween,
+}
+
+/// Event sent when an animation finish.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct AnimationFinished {
+    /// Entity that was animated.
+    pub entity: Entity,
+    /// Kind of animation that finished.
+    pub kind: AnimationKind,
+}
+
+/// Component running a registered system with the animated entity as input once the next
+/// animation of a kind finish on this entity. Removed once run.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct FollowUp {
+    /// Kind of animation to wait for.
+    pub kind: AnimationKind,
+    /// System to run, given the animated entity.
+    pub system: SystemId<In<Entity>>,
+}
+
+impl FollowUp {
+    /// Create a new [`FollowUp`] running a system once an animation of a kind finish.
+    #[must_use]
+    pub fn new(kind: AnimationKind, system: SystemId<In<Entity>>) -> Self {
+        FollowUp { kind, system }
+    }
+}
+
 /// Animation for object transform. Easing between 2 transform value.
 #[derive(Component, Clone, Copy)]
 #[require(GridTransform)]
@@ -19,12 +90,16 @@ pub struct TransformAnimation {
     progress: Duration,
 
     function: EaseFunction,
+
+    /// World position to ease from instead of `old_transform`, when the animation interrupted
+    /// another one.
+    interrupted_at: Option<Vec2>,
 }
 
 impl TransformAnimation {
     /// Create a new [`TransformAnimation`] component with a given `function`.
-    #[allow(unused)]
-    fn with_function(function: EaseFunction) -> Self {
+    #[must_use]
+    pub fn with_function(function: EaseFunction) -> Self {
         TransformAnimation {
             function,
             ..Default::default()
@@ -32,6 +107,33 @@ impl TransformAnimation {
     }
 }
 
+impl TransformAnimation {
+    /// Start a new animation from a transform, replacing any animation in progress. An animation
+    /// in progress is continued from the position it was at, `from` being its target. The
+    /// interrupted animation does not send an [`AnimationFinished`] event.
+    pub fn start(&mut self, from: GridTransform, duration: Duration) {
+        self.interrupted_at = (!self.duration.is_zero()).then(|| self.position(&from, 0.0).xy());
+        self.old_transform = from;
+        self.duration = duration;
+        self.progress = Duration::ZERO;
+    }
+
+    /// Get the eased position toward a target at the current progress.
+    fn position(&self, target: &GridTransform, z: f32) -> Vec3 {
+        let from = self
+            .interrupted_at
+            .map_or_else(|| self.old_transform.as_vec3_with_z(z), |p| p.extend(z));
+
+        let t = if self.duration.is_zero() {
+            1.0
+        } else {
+            (self.progress.as_secs_f32() / self.duration.as_secs_f32()).clamp(0.0, 1.0)
+        };
+
+        EasingCurve::new(from, target.as_vec3_with_z(z), self.function).sample_clamped(t)
+    }
+}
+
 impl Default for TransformAnimation {
     fn default() -> Self {
         TransformAnimation {
@@ -39,16 +141,24 @@ impl Default for TransformAnimation {
             duration: Duration::default(),
             progress: Duration::default(),
             function: EaseFunction::Linear,
+            interrupted_at: None,
         }
     }
 }
 
 /// System to handle [`TransformAnimation`] and animate them.
 pub fn transform_animation(
-    fixed_time: Res<Time<Fixed>>,
-    mut data: Query<(&mut Transform, &GridTransform, &mut TransformAnimation)>,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    mut finished: EventWriter<AnimationFinished>,
+    mut data: Query<(
+        Entity,
+        &mut Transform,
+        &GridTransform,
+        &mut TransformAnimation,
+    )>,
 ) {
-    for (mut transform, grid, mut transform_animation) in &mut data {
+    for (entity, mut transform, grid, mut transform_animation) in &mut data {
         // skip if there no animation
         if transform_animation.duration.is_zero() {
             transform.translation = grid.as_vec3_with_z(transform.translation.z);
@@ -56,33 +166,41 @@ pub fn transform_animation(
         }
 
         // Increase the progress
-        transform_animation.progress += fixed_time.delta();
-
-        let TransformAnimation {
-            old_transform,
-            duration,
-            progress,
-            function,
-        } = *transform_animation;
+        transform_animation.progress += time_scale.delta(&time);
 
         let z = transform.translation.z;
+        transform.translation = transform_animation.position(grid, z);
 
-        // create a easing curve
-        let curve = EasingCurve::new(
-            old_transform.as_vec3_with_z(z),
-            grid.as_vec3_with_z(z),
-            function,
-        )
-        .reparametrize_linear(interval(0.0, 1.0).unwrap()) // map to between 0 and 1
-        .unwrap();
-
-        let t = (progress.as_secs_f32() / duration.as_secs_f32()).clamp(0.0, 1.0);
-
-        transform.translation = curve.sample(t).unwrap();
-
-        if progress >= duration {
+        if transform_animation.progress >= transform_animation.duration {
+            // land exactly on the target whatever the easing function
+            transform.translation = grid.as_vec3_with_z(z);
             transform_animation.duration = Duration::ZERO;
             transform_animation.progress = Duration::ZERO;
+            transform_animation.interrupted_at = None;
+
+            finished.send(AnimationFinished {
+                entity,
+                kind: AnimationKind::Transform,
+            });
+        }
+    }
+}
+
+/// System to run the [`FollowUp`] of every [`AnimationFinished`].
+pub fn run_follow_ups(
+    mut commands: Commands,
+    mut finished: EventReader<AnimationFinished>,
+    follow_ups: Query<&FollowUp>,
+) {
+    for AnimationFinished { entity, kind } in finished.read().copied() {
+        let Ok(follow_up) = follow_ups.get(entity) else {
+            continue;
+        };
+        if follow_up.kind != kind {
+            continue;
         }
+
+        commands.entity(entity).remove::<FollowUp>();
+        commands.run_system_with_input(follow_up.system, entity);
     }
 }
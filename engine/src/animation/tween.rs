@@ -0,0 +1,458 @@
+//! Tween animating transform and sprite colour.
+//!
+//! A [`Tweenable`] is a tree of [`Tween`], combined into sequence, parallel group, delay and
+//! repeat. It is played by adding an [`Animator`] component, which is removed by the
+//! [`tween_animation`] system once done, sending an [`AnimationFinished`] event.
+//!
+//! The translation of a tween is an offset from the [`GridTransform`] position, so it can play on
+//! top of a [`TransformAnimation`] and always end back on the grid.
+
+use std::time::Duration;
+
+use bevy::color::Mix;
+use bevy::ecs::component::ComponentId;
+use bevy::ecs::world::DeferredWorld;
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+/// Property animated by a [`Tween`], with the start and end value.
+#[derive(Clone, Copy, Debug)]
+pub enum TweenProperty {
+    /// Offset from the grid position.
+    Translation(Vec3, Vec3),
+    /// Rotation of the [`Transform`].
+    Rotation(Quat, Quat),
+    /// Scale of the [`Transform`].
+    Scale(Vec3, Vec3),
+    /// Tint of the [`AtlasSprite`].
+    Color(Color, Color),
+    /// Mix a colour into the tint the [`AtlasSprite`] had when the [`Animator`] started, from a
+    /// factor to another, `0.0` being the original tint.
+    Flash(Color, f32, f32),
+    /// Alpha of the tint the [`AtlasSprite`] had when the [`Animator`] started, multiplied from a
+    /// factor to another.
+    Fade(f32, f32),
+}
+
+/// Single property easing from a value to another.
+#[derive(Clone, Copy, Debug)]
+pub struct Tween {
+    /// Property to animate.
+    pub property: TweenProperty,
+    /// Duration of the tween.
+    pub duration: Duration,
+    /// Easing function.
+    pub function: EaseFunction,
+}
+
+impl Tween {
+    /// Create a new [`Tween`] with a linear easing.
+    #[must_use]
+    pub fn new(property: TweenProperty, duration: Duration) -> Self {
+        Tween {
+            property,
+            duration,
+            function: EaseFunction::Linear,
+        }
+    }
+
+    /// Set the easing function of this [`Tween`].
+    #[must_use]
+    pub fn with_function(self, function: EaseFunction) -> Self {
+        Tween { function, ..self }
+    }
+
+    /// Apply the tween to the values at a time since its start.
+    fn apply(&self, elapsed: Duration, values: &mut TweenValues) {
+        let t = if self.duration.is_zero() {
+            1.0
+        } else {
+            (elapsed.as_secs_f32() / self.duration.as_secs_f32()).clamp(0.0, 1.0)
+        };
+        let t = EasingCurve::new(0.0, 1.0, self.function).sample_clamped(t);
+
+        match self.property {
+            TweenProperty::Translation(from, to) => values.offset = Some(from.lerp(to, t)),
+            TweenProperty::Rotation(from, to) => values.rotation = Some(from.slerp(to, t)),
+            TweenProperty::Scale(from, to) => values.scale = Some(from.lerp(to, t)),
+            TweenProperty::Color(from, to) => values.color = Some(from.mix(&to, t)),
+            TweenProperty::Flash(color, from, to) => {
+                values.color = Some(values.base_color.mix(&color, from.lerp(to, t)));
+            }
+            TweenProperty::Fade(from, to) => {
+                let alpha = values.base_color.alpha() * from.lerp(to, t);
+                values.color = Some(values.base_color.with_alpha(alpha));
+            }
+        }
+    }
+}
+
+/// Value of every property at a point of a [`Tweenable`]. [`None`] if the property is not
+/// animated yet.
+struct TweenValues {
+    base_color: Color,
+    offset: Option<Vec3>,
+    rotation: Option<Quat>,
+    scale: Option<Vec3>,
+    color: Option<Color>,
+}
+
+/// Tree of [`Tween`] that can be played by an [`Animator`].
+#[derive(Clone, Debug)]
+pub enum Tweenable {
+    /// Single tween.
+    Tween(Tween),
+    /// Wait without animating anything.
+    Delay(Duration),
+    /// Play each child one after the other.
+    Sequence(Vec<Tweenable>),
+    /// Play every child at the same time, until the longest is done.
+    Parallel(Vec<Tweenable>),
+    /// Play the child a number of time, or forever if [`None`].
+    Repeat(Box<Tweenable>, Option<u32>),
+}
+
+impl From<Tween> for Tweenable {
+    fn from(tween: Tween) -> Self {
+        Tweenable::Tween(tween)
+    }
+}
+
+impl Tweenable {
+    /// Play another tweenable after this one.
+    #[must_use]
+    pub fn then(self, next: impl Into<Tweenable>) -> Self {
+        match self {
+            Tweenable::Sequence(mut children) => {
+                children.push(next.into());
+                Tweenable::Sequence(children)
+            }
+            tweenable => Tweenable::Sequence(vec![tweenable, next.into()]),
+        }
+    }
+
+    /// Play another tweenable at the same time as this one.
+    #[must_use]
+    pub fn with(self, other: impl Into<Tweenable>) -> Self {
+        match self {
+            Tweenable::Parallel(mut children) => {
+                children.push(other.into());
+                Tweenable::Parallel(children)
+            }
+            tweenable => Tweenable::Parallel(vec![tweenable, other.into()]),
+        }
+    }
+
+    /// Wait before playing this tweenable.
+    #[must_use]
+    pub fn delayed(self, delay: Duration) -> Self {
+        Tweenable::Delay(delay).then(self)
+    }
+
+    /// Repeat this tweenable a number of time, or forever if [`None`].
+    #[must_use]
+    pub fn repeat(self, count: Option<u32>) -> Self {
+        Tweenable::Repeat(Box::new(self), count)
+    }
+
+    /// Total duration, [`None`] if it repeat forever.
+    #[must_use]
+    pub fn duration(&self) -> Option<Duration> {
+        match self {
+            Tweenable::Tween(tween) => Some(tween.duration),
+            Tweenable::Delay(delay) => Some(*delay),
+            Tweenable::Sequence(children) => children.iter().map(Tweenable::duration).sum(),
+            Tweenable::Parallel(children) => children
+                .iter()
+                .map(Tweenable::duration)
+                .try_fold(Duration::ZERO, |max, d| Some(max.max(d?))),
+            Tweenable::Repeat(child, count) => Some(child.duration()? * (*count)?),
+        }
+    }
+
+    /// Apply every tween to the values at a time since the start. Tween that are done keep their
+    /// end value and tween not started yet are skipped.
+    fn apply(&self, elapsed: Duration, values: &mut TweenValues) {
+        match self {
+            Tweenable::Tween(tween) => tween.apply(elapsed, values),
+            // repeating 0 time play nothing
+            Tweenable::Delay(_) | Tweenable::Repeat(_, Some(0)) => (),
+            Tweenable::Sequence(children) => {
+                let mut start = Duration::ZERO;
+                for child in children {
+                    if elapsed < start {
+                        break;
+                    }
+                    child.apply(elapsed - start, values);
+                    start = start.saturating_add(child.duration().unwrap_or(Duration::MAX));
+                }
+            }
+            Tweenable::Parallel(children) => {
+                for child in children {
+                    child.apply(elapsed, values);
+                }
+            }
+            Tweenable::Repeat(child, count) => {
+                let elapsed = match child.duration().filter(|d| !d.is_zero()) {
+                    Some(duration) if count.is_some_and(|count| elapsed >= duration * count) => {
+                        duration
+                    }
+                    Some(duration) => {
+                        Duration::from_secs_f64(elapsed.as_secs_f64() % duration.as_secs_f64())
+                    }
+                    None => elapsed,
+                };
+                child.apply(elapsed, values);
+            }
+        }
+    }
+
+    /// Nudge toward a direction and back, such as when bumping into a wall.
+    #[must_use]
+    pub fn bump(direction: Direction) -> Self {
+        Tweenable::lunge_by(direction, 2.0, Duration::from_millis(60))
+    }
+
+    /// Lunge half a tile toward a direction and back, such as when attacking.
+    #[must_use]
+    pub fn lunge(direction: Direction) -> Self {
+        Tweenable::lunge_by(
+            direction,
+            f32::from(TILE_SIZE) / 2.0,
+            Duration::from_millis(80),
+        )
+    }
+
+    /// Move some distance toward a direction and back.
+    fn lunge_by(direction: Direction, distance: f32, duration: Duration) -> Self {
+        // the grid y axis point down, the world y axis point up
+        let offset = (direction.offset().as_vec2() * Vec2::new(1.0, -1.0) * distance).extend(0.0);
+
+        Tweenable::from(
+            Tween::new(TweenProperty::Translation(Vec3::ZERO, offset), duration)
+                .with_function(EaseFunction::QuadraticOut),
+        )
+        .then(
+            Tween::new(TweenProperty::Translation(offset, Vec3::ZERO), duration)
+                .with_function(EaseFunction::QuadraticIn),
+        )
+    }
+
+    /// Flash the sprite with a colour and back to its tint, such as when getting hit.
+    #[must_use]
+    pub fn hit_flash(color: Color) -> Self {
+        let duration = Duration::from_millis(60);
+
+        Tweenable::from(Tween::new(TweenProperty::Flash(color, 0.0, 1.0), duration))
+            .then(Tween::new(TweenProperty::Flash(color, 1.0, 0.0), duration))
+            .repeat(Some(2))
+    }
+
+    /// Shrink and fade out the sprite, such as when dying.
+    #[must_use]
+    pub fn death() -> Self {
+        let duration = Duration::from_millis(300);
+
+        Tweenable::from(
+            Tween::new(TweenProperty::Scale(Vec3::ONE, Vec3::ZERO), duration)
+                .with_function(EaseFunction::BackIn),
+        )
+        .with(Tween::new(TweenProperty::Fade(1.0, 0.0), duration))
+    }
+}
+
+/// Component playing a [`Tweenable`].
+///
+/// Replacing or removing an [`Animator`] before it is done restore the tint the sprite had when
+/// it started, so an interrupted colour tween does not leave the sprite tinted.
+#[derive(Component, Clone, Debug)]
+#[require(GridTransform)]
+#[component(on_replace = restore_tint)]
+pub struct Animator {
+    tweenable: Tweenable,
+    elapsed: Duration,
+    /// Tint of the sprite when the animator started, used by relative colour tween.
+    base_tint: Option<Color>,
+}
+
+impl Animator {
+    /// Create a new [`Animator`] playing a tweenable from the start.
+    #[must_use]
+    pub fn new(tweenable: impl Into<Tweenable>) -> Self {
+        Animator {
+            tweenable: tweenable.into(),
+            elapsed: Duration::ZERO,
+            base_tint: None,
+        }
+    }
+
+    /// Return if the tweenable is done playing.
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        self.tweenable.duration().is_some_and(|d| self.elapsed >= d)
+    }
+}
+
+/// Hook to restore the tint of the sprite when an [`Animator`] is replaced or removed.
+fn restore_tint(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+    let Some(tint) = world.get::<Animator>(entity).and_then(|a| a.base_tint) else {
+        return;
+    };
+
+    if let Some(mut sprite) = world.get_mut::<AtlasSprite>(entity) {
+        sprite.tint = tint;
+    }
+}
+
+/// System to play every [`Animator`]. Need to run after [`update_transform`] and
+/// [`transform_animation`], as the translation is an offset from the position they set.
+pub fn tween_animation(
+    mut commands: Commands,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    mut finished: EventWriter<AnimationFinished>,
+    mut animators: Query<(
+        Entity,
+        &mut Animator,
+        &mut Transform,
+        Option<&mut AtlasSprite>,
+    )>,
+) {
+    for (entity, mut animator, mut transform, sprite) in &mut animators {
+        animator.elapsed += time_scale.delta(&time);
+
+        let tint = sprite.as_ref().map_or(Color::WHITE, |s| s.tint);
+        let mut values = TweenValues {
+            base_color: *animator.base_tint.get_or_insert(tint),
+            offset: None,
+            rotation: None,
+            scale: None,
+            color: None,
+        };
+        animator.tweenable.apply(animator.elapsed, &mut values);
+
+        if let Some(offset) = values.offset {
+            transform.translation += offset;
+        }
+        if let Some(rotation) = values.rotation {
+            transform.rotation = rotation;
+        }
+        if let Some(scale) = values.scale {
+            transform.scale = scale;
+        }
+        if let (Some(color), Some(mut sprite)) = (values.color, sprite) {
+            sprite.tint = color;
+        }
+
+        if animator.is_done() {
+            // keep the end value of a finished animator
+            animator.base_tint = None;
+            commands.entity(entity).remove::<Animator>();
+            finished.send(AnimationFinished {
+                entity,
+                kind: AnimationKind::Tween,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    /// Tween moving along the x axis from `from` to `to`.
+    fn slide(from: f32, to: f32, millis: u64) -> Tweenable {
+        Tween::new(
+            TweenProperty::Translation(Vec3::X * from, Vec3::X * to),
+            ms(millis),
+        )
+        .into()
+    }
+
+    /// Get the x offset of a tweenable at a time, [`None`] if nothing is applied.
+    fn offset_at(tweenable: &Tweenable, millis: u64) -> Option<f32> {
+        let mut values = TweenValues {
+            base_color: Color::WHITE,
+            offset: None,
+            rotation: None,
+            scale: None,
+            color: None,
+        };
+        tweenable.apply(ms(millis), &mut values);
+        values.offset.map(|o| o.x)
+    }
+
+    #[test]
+    fn duration_of_combinators() {
+        let sequence = slide(0.0, 1.0, 100).then(slide(1.0, 0.0, 50));
+        assert_eq!(sequence.duration(), Some(ms(150)));
+
+        let parallel = slide(0.0, 1.0, 100).with(slide(0.0, 1.0, 30));
+        assert_eq!(parallel.duration(), Some(ms(100)));
+
+        assert_eq!(
+            slide(0.0, 1.0, 100).delayed(ms(20)).duration(),
+            Some(ms(120))
+        );
+        assert_eq!(sequence.clone().repeat(Some(3)).duration(), Some(ms(450)));
+        assert_eq!(
+            sequence.clone().repeat(Some(0)).duration(),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn duration_of_endless_repeat() {
+        let endless = slide(0.0, 1.0, 100).repeat(None);
+        assert_eq!(endless.duration(), None);
+        assert_eq!(endless.clone().then(slide(0.0, 1.0, 10)).duration(), None);
+        assert_eq!(endless.with(slide(0.0, 1.0, 10)).duration(), None);
+    }
+
+    #[test]
+    fn apply_sequence() {
+        let sequence = slide(0.0, 10.0, 100).then(slide(10.0, 0.0, 100));
+
+        assert_eq!(offset_at(&sequence, 50), Some(5.0));
+        assert_eq!(offset_at(&sequence, 100), Some(10.0));
+        assert_eq!(offset_at(&sequence, 150), Some(5.0));
+        assert_eq!(offset_at(&sequence, 500), Some(0.0));
+    }
+
+    #[test]
+    fn apply_delay_and_parallel() {
+        let delayed = slide(0.0, 10.0, 100).delayed(ms(50));
+        assert_eq!(offset_at(&delayed, 20), None);
+        assert_eq!(offset_at(&delayed, 100), Some(5.0));
+
+        // the later child win when both animate the same property
+        let parallel = slide(0.0, 10.0, 100).with(slide(0.0, 20.0, 50));
+        assert_eq!(offset_at(&parallel, 25), Some(10.0));
+        assert_eq!(offset_at(&parallel, 75), Some(20.0));
+    }
+
+    #[test]
+    fn apply_repeat() {
+        let repeat = slide(0.0, 10.0, 100).repeat(Some(2));
+        assert_eq!(offset_at(&repeat, 50), Some(5.0));
+        assert_eq!(offset_at(&repeat, 150), Some(5.0));
+        // done repeating, keep the end value
+        assert_eq!(offset_at(&repeat, 250), Some(10.0));
+
+        let endless = slide(0.0, 10.0, 100).repeat(None);
+        assert_eq!(offset_at(&endless, 1050), Some(5.0));
+    }
+
+    #[test]
+    fn apply_repeat_zero_time() {
+        let repeat = slide(0.0, 10.0, 100).repeat(Some(0));
+        assert_eq!(offset_at(&repeat, 0), None);
+        assert_eq!(offset_at(&repeat, 50), None);
+        assert!(Animator::new(repeat).is_done());
+    }
+}
@@ -3,15 +3,35 @@
 //! The engine first load a global [`Atlas`] resource that contain an atlas texture and a
 //! preconfigure [`TextureAtlasLayout`]. This atlas can also be index using a [`Texture`]
 //!
+//! Every atlas is stored by name in the [`GlobalAtlas`] resource and each [`Texture`] is dispatch
+//! to the atlas named by [`Texture::atlas`], so adding a new sheet only need a new name.
+//!
+//! Instead of a hand made sheet, an atlas can also be pack at startup from a folder of individual
+//! sprite using a [`SpriteFolder`]. Each sprite is then index by its file name, see
+//! [`Texture::name`].
+//!
 //! The engine also provide the [`AtlasSprite`] component that interact with the [`Atlas`] resource
 //! to render sprite. The component is simply an auxilary component that is use to update the
 //! [`Sprite`] on the same entity. This update happen on the [`atlas_to_sprite`] system.
+//!
+//! Sprite variant can be made without duplicating art by giving the [`AtlasSprite`] a tint or a
+//! [`Palette`] to swap colour with. Palette swapped texture are cached in the [`PaletteCache`].
+//!
+//! The palette swap is done on the CPU, making a recoloured copy of the atlas texture once per
+//! palette, instead of in a shader material. Sprite keep using the default [`Sprite`] pipeline,
+//! so they are still batched together and keep their [`TextureAtlas`], flip and tint, which a
+//! custom `Material2d` on a mesh would all need to reimplement. The atlas are small pixel art
+//! sheet and only a few palette are used, so the extra copy are cheap.
 
+use bevy::asset::LoadedFolder;
 use bevy::prelude::*;
+use bevy::utils::HashMap;
 
+mod palette;
 mod system;
 mod texture;
 
+pub use palette::*;
 pub use system::*;
 pub use texture::*;
 
@@ -19,64 +39,93 @@ pub use texture::*;
 pub struct Atlas {
     texture: Handle<Image>,
     layout: Handle<TextureAtlasLayout>,
+    names: Option<HashMap<String, usize>>,
 }
 
 impl Atlas {
     /// Create a new atlas resource using a given texture and a layout
     #[must_use]
     pub fn new(texture: Handle<Image>, layout: Handle<TextureAtlasLayout>) -> Self {
-        Atlas { texture, layout }
+        Atlas {
+            texture,
+            layout,
+            names: None,
+        }
+    }
+
+    /// Create a new atlas resource where each sprite is index using the name of the [`Texture`]
+    /// instead of its position, see [`Texture::name`].
+    #[must_use]
+    pub fn with_names(
+        texture: Handle<Image>,
+        layout: Handle<TextureAtlasLayout>,
+        names: HashMap<String, usize>,
+    ) -> Self {
+        Atlas {
+            texture,
+            layout,
+            names: Some(names),
+        }
     }
 
     /// Get the sprite texture data from the atlas.
     /// Return a [`Handle<Image>`] pointing to the atlas texture and a [`TextureAtlas`] configure
     /// to the correct position within the texture.
+    ///
+    /// Return [`None`] if the atlas is index by name and do not have a sprite for the texture.
     #[must_use]
-    pub fn get_sprite_data(&self, texture: Texture) -> (Handle<Image>, TextureAtlas) {
-        (
+    pub fn get_sprite_data(&self, texture: Texture) -> Option<(Handle<Image>, TextureAtlas)> {
+        let index = match &self.names {
+            Some(names) => *names.get(texture.name()?)?,
+            None => texture.into(),
+        };
+
+        Some((
             self.texture.clone(),
             TextureAtlas {
                 layout: self.layout.clone(),
-                index: texture.into(),
+                index,
             },
-        )
+        ))
     }
 }
 
-/// Use to index into the global atlas
-#[repr(usize)]
-#[allow(missing_docs)]
-pub enum GlobalAtlasIndex {
-    Main = 0,
-    Wall,
-}
-
 /// Global resource for atlas.
 /// This hold all [`Handle<Image>`] pointing to the texture for a atlas and all
 /// [`Handle<TextureAtlaLayout>`] configure to the atlast config.
+///
+/// Each atlas is keyed by a name, a [`Texture`] know which atlas it belong to using
+/// [`Texture::atlas`].
 #[derive(Resource, Default)]
 pub struct GlobalAtlas {
-    atlas: Vec<Atlas>,
+    atlas: HashMap<String, Atlas>,
 }
 
 impl GlobalAtlas {
     /// Create a new global atlas resource.
     #[must_use]
     pub fn new() -> Self {
-        GlobalAtlas { atlas: vec![] }
+        GlobalAtlas {
+            atlas: HashMap::new(),
+        }
     }
 
-    /// Add new atlas into the list
-    pub fn add_atlas(&mut self, atlas: Atlas) {
-        self.atlas.push(atlas);
+    /// Add new atlas with a name, replacing any atlas with the same name.
+    pub fn add_atlas(&mut self, name: impl Into<String>, atlas: Atlas) {
+        self.atlas.insert(name.into(), atlas);
     }
 
-    fn sprite_from_atlas(
-        &self,
-        index: GlobalAtlasIndex,
-        texture: Texture,
-    ) -> (Handle<Image>, TextureAtlas) {
-        self.atlas[index as usize].get_sprite_data(texture)
+    /// Get an atlas using its name.
+    #[must_use]
+    pub fn get_atlas(&self, name: &str) -> Option<&Atlas> {
+        self.atlas.get(name)
+    }
+
+    /// Get the sprite texture data for a texture from the atlas it belong to. Return [`None`] if
+    /// that atlas have not been added.
+    #[must_use]
+    pub fn sprite_from_atlas(&self, texture: Texture) -> Option<(Handle<Image>, TextureAtlas)> {
+        self.get_atlas(texture.atlas())?.get_sprite_data(texture)
     }
 }
 
@@ -90,6 +139,10 @@ pub struct AtlasSprite {
     pub flip_x: bool,
     /// Flip the sprite across the y axis
     pub flip_y: bool,
+    /// Colour to tint the sprite with.
+    pub tint: Color,
+    /// Optional palette to swap the sprite colour with.
+    pub palette: Option<Handle<Palette>>,
 }
 
 impl AtlasSprite {
@@ -100,6 +153,44 @@ impl AtlasSprite {
             texture,
             flip_x: false,
             flip_y: false,
+            tint: Color::WHITE,
+            palette: None,
+        }
+    }
+
+    /// Set the tint of this [`AtlasSprite`].
+    #[must_use]
+    pub fn with_tint(self, tint: Color) -> AtlasSprite {
+        AtlasSprite { tint, ..self }
+    }
+
+    /// Set the palette of this [`AtlasSprite`].
+    #[must_use]
+    pub fn with_palette(self, palette: Handle<Palette>) -> AtlasSprite {
+        AtlasSprite {
+            palette: Some(palette),
+            ..self
+        }
+    }
+}
+
+/// Folder of individual sprite file to pack into an atlas at startup. Processed and despawned by
+/// the [`pack_sprite_folder`] system once every sprite in the folder is loaded.
+#[derive(Component)]
+pub struct SpriteFolder {
+    /// Name of the atlas to add to the [`GlobalAtlas`], replacing any atlas with the same name.
+    pub name: String,
+    /// Folder containing the sprite.
+    pub folder: Handle<LoadedFolder>,
+}
+
+impl SpriteFolder {
+    /// Create a new [`SpriteFolder`] to pack into the atlas with the given name.
+    #[must_use]
+    pub fn new(name: impl Into<String>, folder: Handle<LoadedFolder>) -> Self {
+        SpriteFolder {
+            name: name.into(),
+            folder,
         }
     }
 }
@@ -0,0 +1,193 @@
+use bevy::{prelude::*, utils::HashMap};
+
+/// Palette swap asset. Map a source colour in an atlas texture to a target colour.
+///
+/// Colours are compared in 8 bit sRGBA so the swap is exact to what the artist painted.
+#[derive(Asset, TypePath, Clone, Debug, Default)]
+pub struct Palette {
+    swaps: HashMap<[u8; 4], [u8; 4]>,
+}
+
+impl Palette {
+    /// Create a new [`Palette`] from a list of `(source, target)` colours.
+    #[must_use]
+    pub fn new(swaps: impl IntoIterator<Item = (Color, Color)>) -> Self {
+        let mut palette = Palette::default();
+        for (source, target) in swaps {
+            palette.swap(source, target);
+        }
+        palette
+    }
+
+    /// Add a colour swap to the palette, replacing any swap for the same source colour.
+    pub fn swap(&mut self, source: Color, target: Color) {
+        self.swaps.insert(
+            source.to_srgba().to_u8_array(),
+            target.to_srgba().to_u8_array(),
+        );
+    }
+
+    /// Create a copy of `image` with every swapped colour replaced.
+    fn apply(&self, image: &Image) -> Image {
+        let mut swapped = image.clone();
+        let size = image.size();
+
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let Ok(color) = image.get_color_at(x, y) else {
+                    continue;
+                };
+
+                if let Some(target) = self.swaps.get(&color.to_srgba().to_u8_array()) {
+                    let [r, g, b, a] = *target;
+                    swapped
+                        .set_color_at(x, y, Color::srgba_u8(r, g, b, a))
+                        .unwrap();
+                }
+            }
+        }
+
+        swapped
+    }
+}
+
+/// Cache of atlas texture with a [`Palette`] applied to them.
+///
+/// Each pair of texture and palette is only recoloured once, the first time a sprite need it. An
+/// entry is dropped by [`invalidate_palette_cache`] when its texture or palette is modified or
+/// removed, so it is recoloured again.
+#[derive(Resource, Default)]
+pub struct PaletteCache(HashMap<(AssetId<Image>, AssetId<Palette>), Handle<Image>>);
+
+impl PaletteCache {
+    /// Get the texture with the palette applied to it, creating it if needed.
+    /// Return [`None`] if either the texture or the palette have not finish loading.
+    pub fn get_or_create(
+        &mut self,
+        image: &Handle<Image>,
+        palette: &Handle<Palette>,
+        images: &mut Assets<Image>,
+        palettes: &Assets<Palette>,
+    ) -> Option<Handle<Image>> {
+        let key = (image.id(), palette.id());
+
+        if let Some(swapped) = self.0.get(&key) {
+            return Some(swapped.clone());
+        }
+
+        let swapped = palettes.get(palette)?.apply(images.get(image)?);
+        let handle = images.add(swapped);
+        self.0.insert(key, handle.clone());
+
+        Some(handle)
+    }
+
+    /// Drop every texture recoloured from an image.
+    pub fn invalidate_image(&mut self, image: AssetId<Image>) {
+        self.0.retain(|(i, _), _| *i != image);
+    }
+
+    /// Drop every texture recoloured with a palette.
+    pub fn invalidate_palette(&mut self, palette: AssetId<Palette>) {
+        self.0.retain(|(_, p), _| *p != palette);
+    }
+}
+
+/// System to drop the [`PaletteCache`] entries whose texture or palette was modified or removed.
+pub fn invalidate_palette_cache(
+    mut palette_events: EventReader<AssetEvent<Palette>>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+    mut palette_cache: ResMut<PaletteCache>,
+) {
+    for event in palette_events.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = *event {
+            palette_cache.invalidate_palette(id);
+        }
+    }
+    for event in image_events.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = *event {
+            palette_cache.invalidate_image(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    };
+
+    use super::*;
+
+    /// Create a 2 by 1 image, red on the left and blue on the right.
+    fn red_blue() -> Image {
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: 2,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[255, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        image.set_color_at(1, 0, Color::srgb_u8(0, 0, 255)).unwrap();
+        image
+    }
+
+    #[test]
+    fn swap_only_matching_colour() {
+        let palette = Palette::new([(Color::srgb_u8(255, 0, 0), Color::srgb_u8(0, 255, 0))]);
+        let swapped = palette.apply(&red_blue());
+
+        assert_eq!(
+            swapped.get_color_at(0, 0).unwrap().to_srgba().to_u8_array(),
+            [0, 255, 0, 255]
+        );
+        assert_eq!(
+            swapped.get_color_at(1, 0).unwrap().to_srgba().to_u8_array(),
+            [0, 0, 255, 255]
+        );
+    }
+
+    #[test]
+    fn later_swap_replace_earlier() {
+        let red = Color::srgb_u8(255, 0, 0);
+        let mut palette = Palette::new([(red, Color::srgb_u8(0, 255, 0))]);
+        palette.swap(red, Color::srgb_u8(255, 255, 0));
+        let swapped = palette.apply(&red_blue());
+
+        assert_eq!(
+            swapped.get_color_at(0, 0).unwrap().to_srgba().to_u8_array(),
+            [255, 255, 0, 255]
+        );
+    }
+
+    #[test]
+    fn cache_reuse_until_invalidated() {
+        let mut images = Assets::<Image>::default();
+        let mut palettes = Assets::<Palette>::default();
+        let image = images.add(red_blue());
+        let palette = palettes.add(Palette::default());
+        let mut cache = PaletteCache::default();
+
+        let first = cache
+            .get_or_create(&image, &palette, &mut images, &palettes)
+            .unwrap();
+        let second = cache
+            .get_or_create(&image, &palette, &mut images, &palettes)
+            .unwrap();
+        assert_eq!(first, second);
+
+        cache.invalidate_palette(palette.id());
+        let third = cache
+            .get_or_create(&image, &palette, &mut images, &palettes)
+            .unwrap();
+        assert_ne!(first, third);
+
+        cache.invalidate_image(image.id());
+        assert!(cache.0.is_empty());
+    }
+}
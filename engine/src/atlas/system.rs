@@ -3,7 +3,11 @@ use std::{
     io::{Read, Seek, SeekFrom},
 };
 
-use bevy::{asset::io::file::FileAssetReader, prelude::*};
+use bevy::{
+    asset::{io::file::FileAssetReader, LoadedFolder},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
 
 use crate::prelude::*;
 
@@ -71,31 +75,132 @@ pub fn create_global_atlas(mut commands: Commands, asset_server: Res<AssetServer
         Atlas::new(texture, layout)
     };
 
-    global_atlas.add_atlas(main_atlas);
-    global_atlas.add_atlas(wall_atlas);
+    global_atlas.add_atlas("main", main_atlas);
+    global_atlas.add_atlas("wall", wall_atlas);
 
     commands.insert_resource(global_atlas);
+    commands.insert_resource(PaletteCache::default());
+}
+
+/// Pack every loaded [`SpriteFolder`] into an atlas and add it to the [`GlobalAtlas`].
+///
+/// Sprite are pack with 2 pixel of padding, and are named after their file name without the
+/// extension.
+pub fn pack_sprite_folder(
+    mut commands: Commands,
+    mut atlas: ResMut<GlobalAtlas>,
+    mut images: ResMut<Assets<Image>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+    loaded_folders: Res<Assets<LoadedFolder>>,
+    sprite_folders: Query<(Entity, &SpriteFolder)>,
+) {
+    for (entity, sprite_folder) in &sprite_folders {
+        let Some(loaded_folder) = loaded_folders.get(&sprite_folder.folder) else {
+            continue;
+        };
+
+        let sprites: Vec<(String, Handle<Image>)> = loaded_folder
+            .handles
+            .iter()
+            .filter_map(|handle| {
+                let handle = handle.clone().try_typed::<Image>().ok()?;
+                let name = handle.path()?.path().file_stem()?.to_str()?.to_string();
+                Some((name, handle))
+            })
+            .collect();
+
+        // wait until every sprite is loaded
+        if sprites.iter().any(|(_, handle)| !images.contains(handle)) {
+            continue;
+        }
+
+        let mut builder = TextureAtlasBuilder::default();
+        builder.padding(UVec2::ONE * 2);
+
+        for (_, handle) in &sprites {
+            builder.add_texture(Some(handle.id()), images.get(handle).unwrap());
+        }
+
+        let (layout, sources, texture) = match builder.build() {
+            Ok(packed) => packed,
+            Err(err) => {
+                error!("Could not pack sprite folder {}: {err}", sprite_folder.name);
+                commands.entity(entity).despawn();
+                continue;
+            }
+        };
+
+        let names: HashMap<String, usize> = sprites
+            .into_iter()
+            .filter_map(|(name, handle)| Some((name, sources.texture_index(&handle)?)))
+            .collect();
+
+        atlas.add_atlas(
+            sprite_folder.name.clone(),
+            Atlas::with_names(images.add(texture), layouts.add(layout), names),
+        );
+
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Get the asset an asset event is about.
+fn event_id<A: Asset>(event: &AssetEvent<A>) -> AssetId<A> {
+    match *event {
+        AssetEvent::Added { id }
+        | AssetEvent::Modified { id }
+        | AssetEvent::Removed { id }
+        | AssetEvent::Unused { id }
+        | AssetEvent::LoadedWithDependencies { id } => id,
+    }
 }
 
-/// Convert atlas data to sprite.
-pub fn atlas_to_sprite(atlas: Res<GlobalAtlas>, mut query: Query<(&mut Sprite, &AtlasSprite)>) {
+/// Convert atlas data to sprite. Only sprite whose [`AtlasSprite`] changed are updated, unless
+/// the [`GlobalAtlas`] changed, or an asset event happened for their atlas texture or palette, as
+/// a palette swapped texture may then need to be created or replaced.
+pub fn atlas_to_sprite(
+    atlas: Res<GlobalAtlas>,
+    mut palette_cache: ResMut<PaletteCache>,
+    mut images: ResMut<Assets<Image>>,
+    palettes: Res<Assets<Palette>>,
+    mut palette_events: EventReader<AssetEvent<Palette>>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+    mut query: Query<(&mut Sprite, Ref<AtlasSprite>)>,
+) {
+    let changed_palettes: HashSet<AssetId<Palette>> = palette_events.read().map(event_id).collect();
+    let changed_images: HashSet<AssetId<Image>> = image_events.read().map(event_id).collect();
+    let atlas_changed = atlas.is_changed();
+
+    let any_asset_event = !changed_palettes.is_empty() || !changed_images.is_empty();
+
     for (mut sprite, atlas_sprite) in &mut query {
-        if let Some(a) = &mut sprite.texture_atlas {
-            a.index = (atlas_sprite.texture).into();
-        } else {
-            let (i, t) = {
-                let t = atlas_sprite.texture;
-                // Dispatch the texture into the correct atlast
-                match t {
-                    Texture::Wall { .. } => atlas.sprite_from_atlas(GlobalAtlasIndex::Wall, t),
-                    _ => atlas.sprite_from_atlas(GlobalAtlasIndex::Main, t),
-                }
-            };
-
-            sprite.image = i;
-            sprite.texture_atlas = Some(t);
+        if !atlas_changed && !any_asset_event && !atlas_sprite.is_changed() {
+            continue;
+        }
+
+        // Dispatch the texture into the correct atlast
+        let Some((i, t)) = atlas.sprite_from_atlas(atlas_sprite.texture) else {
+            continue;
+        };
+
+        let asset_changed = changed_images.contains(&i.id())
+            || atlas_sprite
+                .palette
+                .as_ref()
+                .is_some_and(|p| changed_palettes.contains(&p.id()));
+        if !atlas_changed && !asset_changed && !atlas_sprite.is_changed() {
+            continue;
         }
 
+        // use the recoloured texture if there is a palette, keep the original until it is ready
+        sprite.image = atlas_sprite
+            .palette
+            .as_ref()
+            .and_then(|p| palette_cache.get_or_create(&i, p, &mut images, &palettes))
+            .unwrap_or(i);
+        sprite.texture_atlas = Some(t);
+
+        sprite.color = atlas_sprite.tint;
         sprite.flip_x = atlas_sprite.flip_y;
         sprite.flip_y = atlas_sprite.flip_x;
     }
@@ -1,3 +1,5 @@
+use crate::map::TileSetKind;
+
 /// Enum for texture in the atlas
 #[derive(Clone, Copy)]
 #[allow(missing_docs)]
@@ -21,38 +23,58 @@ pub enum Texture {
     DoorS,
     DoorW,
 
-    Wall {
-        /// Is this the top wall piece
-        top: bool,
-        /// Is this wall piece connected on the side or horizontally
-        horz_wall: bool,
-        /// Is this wall piece connected on the top or vertically
-        vert_wall: bool,
-        /// If this wall piece have a walled corner
-        corner: bool,
+    /// Auto tiled texture from a tile set, see [`TileSet`](crate::map::TileSet).
+    Tile {
+        /// Tile set this texture is from.
+        set: TileSetKind,
+        /// Index of the sprite in the tile set atlas.
+        index: usize,
     },
 }
 
+impl Texture {
+    /// Name of the atlas in the [`GlobalAtlas`](super::GlobalAtlas) this texture belong to.
+    #[must_use]
+    pub fn atlas(self) -> &'static str {
+        match self {
+            Texture::Tile { set, .. } => set.name(),
+            _ => "main",
+        }
+    }
+
+    /// Name of this texture, use to index into atlas pack from individual sprite file. The name
+    /// match the sprite file name without its extension, e.g. `player.png` for
+    /// [`Texture::Player`].
+    ///
+    /// Return [`None`] for auto tiled texture as they do not have a single sprite.
+    #[must_use]
+    pub fn name(self) -> Option<&'static str> {
+        Some(match self {
+            Texture::Player => "player",
+            Texture::Blank => "blank",
+            Texture::Dwarf => "dwarf",
+            Texture::Snake => "snake",
+            Texture::Goblin => "goblin",
+            Texture::Ground => "ground",
+            Texture::Brick => "brick",
+            Texture::Soil => "soil",
+            Texture::Grass => "grass",
+            Texture::Flower => "flower",
+            Texture::Grass2 => "grass2",
+            Texture::Flower2 => "flower2",
+            Texture::DoorN => "door_n",
+            Texture::DoorE => "door_e",
+            Texture::DoorS => "door_s",
+            Texture::DoorW => "door_w",
+            Texture::Tile { .. } => return None,
+        })
+    }
+}
+
 impl From<Texture> for usize {
     fn from(val: Texture) -> Self {
         match val {
-            Texture::Wall {
-                top,
-                horz_wall,
-                vert_wall,
-                corner,
-            } => {
-                let top_offset = if top { 0 } else { 5 };
-
-                if corner && horz_wall && vert_wall {
-                    return top_offset + 4;
-                }
-
-                let horz_offset = if horz_wall { 0 } else { 2 };
-                let vert_offset = if vert_wall { 0 } else { 1 };
-
-                top_offset + horz_offset + vert_offset
-            }
+            Texture::Tile { index, .. } => index,
             // this is literal black magic, I do not know what this mean, any question please
             // consult the rustonomicon
             _ => unsafe { *(&raw const val).cast::<Self>() },
@@ -0,0 +1,138 @@
+//! Distance map (flow field) for AI movement.
+//!
+//! A [`DistanceMap`] store the distance from every reachable position to the closest of one or
+//! more goal. Any number of agent can then approach the goal by stepping to the neighbour with a
+//! lower distance, or flee by stepping to the neighbour with a higher distance, without running
+//! [`find_path`] for each of them.
+//!
+//! The engine keep a [`PlayerDistanceMap`] toward the player, recomputed by the
+//! [`update_player_distance_map`] system only when the player move or the [`Map`] change.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::pathfinding::{step_distance, step_targets};
+use crate::prelude::*;
+
+/// Distance from every reachable position within a bound to the closest goal.
+#[derive(Clone, Debug, Default)]
+pub struct DistanceMap {
+    distances: HashMap<IVec2, u32>,
+    diagonal: bool,
+}
+
+impl DistanceMap {
+    /// Compute a new distance map from a list of goal, only considering position within `bounds`
+    /// (inclusive of both corner). Diagonal step are allowed if `diagonal` is set, following the
+    /// same rule as [`find_path`].
+    #[must_use]
+    pub fn new(
+        map: &Map,
+        goals: impl IntoIterator<Item = IVec2>,
+        bounds: IRect,
+        diagonal: bool,
+    ) -> Self {
+        let in_bounds = |p: IVec2| p.cmpge(bounds.min).all() && p.cmple(bounds.max).all();
+
+        let mut distances = HashMap::new();
+        let mut open = BinaryHeap::new();
+
+        for goal in goals.into_iter().filter(|g| in_bounds(*g)) {
+            distances.insert(goal, 0);
+            open.push(Reverse((0, goal.x, goal.y)));
+        }
+
+        while let Some(Reverse((distance, x, y))) = open.pop() {
+            let position = IVec2::new(x, y);
+
+            if distances.get(&position).is_some_and(|d| *d < distance) {
+                continue;
+            }
+
+            for next in step_targets(map, position, diagonal) {
+                if !in_bounds(next) {
+                    continue;
+                }
+
+                let next_distance = distance + step_distance(position, next, diagonal);
+                if distances.get(&next).is_some_and(|d| *d <= next_distance) {
+                    continue;
+                }
+
+                distances.insert(next, next_distance);
+                open.push(Reverse((next_distance, next.x, next.y)));
+            }
+        }
+
+        DistanceMap {
+            distances,
+            diagonal,
+        }
+    }
+
+    /// Get the distance from a position to the closest goal. Return [`None`] if the position can
+    /// not reach any goal.
+    #[must_use]
+    pub fn get(&self, position: IVec2) -> Option<u32> {
+        self.distances.get(&position).copied()
+    }
+
+    /// Get the neighbour of a position closest to a goal, to approach it. Return [`None`] if the
+    /// position is already at a goal or can not reach one.
+    #[must_use]
+    pub fn descend(&self, map: &Map, position: IVec2) -> Option<IVec2> {
+        let curr = self.get(position)?;
+
+        step_targets(map, position, self.diagonal)
+            .into_iter()
+            .filter_map(|p| Some((self.get(p)?, p)))
+            .filter(|(d, _)| *d < curr)
+            .min_by_key(|(d, _)| *d)
+            .map(|(_, p)| p)
+    }
+
+    /// Get the neighbour of a position furthest from any goal, to flee from them. Return [`None`]
+    /// if there is no neighbour further away.
+    #[must_use]
+    pub fn ascend(&self, map: &Map, position: IVec2) -> Option<IVec2> {
+        let curr = self.get(position)?;
+
+        step_targets(map, position, self.diagonal)
+            .into_iter()
+            .filter_map(|p| Some((self.get(p)?, p)))
+            .filter(|(d, _)| *d > curr)
+            .max_by_key(|(d, _)| *d)
+            .map(|(_, p)| p)
+    }
+}
+
+/// Resource holding a [`DistanceMap`] toward the player over the current and adjacent room.
+#[derive(Resource, Default)]
+pub struct PlayerDistanceMap(pub DistanceMap);
+
+/// System to recompute the [`PlayerDistanceMap`] when the player move, the [`Map`] or the
+/// [`MovementOptions`] change. Diagonal step are used if [`MovementOptions::diagonal`] allow them.
+pub fn update_player_distance_map(
+    map: Res<Map>,
+    options: Res<MovementOptions>,
+    player: Single<Ref<GridTransform>, With<Player>>,
+    mut distance_map: ResMut<PlayerDistanceMap>,
+) {
+    if !map.is_changed() && !player.is_changed() && !options.is_changed() {
+        return;
+    }
+
+    let (x, y) = map.curr_room_pos;
+    let bounds = IRect::from_corners(
+        Map::room_to_world((x - 1, y - 1), UVec2::ZERO),
+        Map::room_to_world(
+            (x + 1, y + 1),
+            UVec2::new((WIDTH - 1).into(), (HEIGHT - 1).into()),
+        ),
+    );
+
+    distance_map.0 = DistanceMap::new(&map, [player.translation], bounds, options.diagonal);
+}
@@ -0,0 +1,98 @@
+//! Facing direction of grid entity.
+//!
+//! The [`Facing`] component is kept up to date by [`resolve_movement`] with the direction of the
+//! last [`MoveIntent`], including one that bumped into something. The [`face_sprite`] system then
+//! flip the [`AtlasSprite`] toward it, or pick the texture from a [`DirectionalSprite`] if the
+//! entity have one.
+
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+/// Direction a grid entity is facing. Never [`Direction::Zero`].
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+#[require(GridTransform)]
+pub struct Facing(Direction);
+
+impl Default for Facing {
+    fn default() -> Self {
+        Facing(Direction::Right)
+    }
+}
+
+impl Facing {
+    /// Create a new [`Facing`] toward a direction. Return [`None`] for [`Direction::Zero`].
+    #[must_use]
+    pub fn new(direction: Direction) -> Option<Self> {
+        (!direction.is_zero()).then_some(Facing(direction))
+    }
+
+    /// Get the direction faced.
+    #[must_use]
+    pub fn direction(self) -> Direction {
+        self.0
+    }
+
+    /// Face toward a direction, ignoring [`Direction::Zero`].
+    pub fn set(&mut self, direction: Direction) {
+        if !direction.is_zero() {
+            self.0 = direction;
+        }
+    }
+
+    /// Get the position directly in front of a position, such as the target of an attack.
+    #[must_use]
+    pub fn front(self, position: IVec2) -> IVec2 {
+        position + self.0.offset()
+    }
+
+    /// Return if a target is within a vision cone in front of a position. `half_angle` is the
+    /// angle in radian between the facing direction and the edge of the cone. The position itself
+    /// is always inside the cone.
+    #[must_use]
+    pub fn in_cone(self, position: IVec2, target: IVec2, half_angle: f32) -> bool {
+        let to_target = (target - position).as_vec2();
+        if to_target == Vec2::ZERO {
+            return true;
+        }
+
+        self.0.offset().as_vec2().angle_to(to_target).abs() <= half_angle
+    }
+}
+
+/// Texture to use for each side an entity can face. Diagonal use the texture of their horizontal
+/// side.
+#[derive(Component, Clone, Copy)]
+#[require(Facing)]
+pub struct DirectionalSprite(pub QuadCompass<Texture>);
+
+/// System to update the [`AtlasSprite`] of entity whose [`Facing`] changed.
+///
+/// Entity with a [`DirectionalSprite`] get the matching texture, other are flipped when facing
+/// left and left alone when facing straight up or down.
+pub fn face_sprite(
+    mut query: Query<(&Facing, &mut AtlasSprite, Option<&DirectionalSprite>), Changed<Facing>>,
+) {
+    for (facing, mut sprite, directional) in &mut query {
+        let direction = facing
+            .direction()
+            .split()
+            .map_or(facing.direction(), |s| s.0);
+
+        if let Some(DirectionalSprite(textures)) = directional {
+            sprite.texture = match direction {
+                Direction::Up => textures.north,
+                Direction::Down => textures.south,
+                Direction::Left => textures.west,
+                _ => textures.east,
+            };
+            continue;
+        }
+
+        match direction {
+            Direction::Left => sprite.flip_y = true,
+            Direction::Right => sprite.flip_y = false,
+            _ => (),
+        }
+    }
+}
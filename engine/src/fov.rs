@@ -0,0 +1,268 @@
+//! Field of view and line of sight on the [`Map`] grid.
+//!
+//! [`compute_fov`] use recursive shadowcasting to find every position visible from an origin,
+//! while [`line_of_sight`] check if a single position can be seen. Both consider a tile to block
+//! sight using [`TileType::blocks_sight`], and position without a room always block sight.
+//!
+//! Entity with a [`Viewshed`] keep their visible position up to date using the
+//! [`update_viewshed`] system. The player viewshed also mark position as explored in the [`Map`].
+
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+use crate::prelude::*;
+
+/// Component holding every position an entity can currently see.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Viewshed {
+    /// How far the entity can see.
+    pub radius: i32,
+    /// Every position currently visible.
+    pub visible: HashSet<IVec2>,
+}
+
+impl Viewshed {
+    /// Create a new [`Viewshed`] with a radius.
+    #[must_use]
+    pub fn new(radius: i32) -> Self {
+        Viewshed {
+            radius,
+            visible: HashSet::new(),
+        }
+    }
+}
+
+/// Multiplier to transform the first octant into each of the 8 octant.
+#[rustfmt::skip]
+static OCTANTS: [(i32, i32, i32, i32); 8] = [
+    (1, 0, 0, 1), (0, 1, 1, 0), (0, -1, 1, 0), (-1, 0, 0, 1),
+    (-1, 0, 0, -1), (0, -1, -1, 0), (0, 1, -1, 0), (1, 0, 0, -1),
+];
+
+fn blocks_sight(map: &Map, position: IVec2) -> bool {
+    map.get_tile(position).is_none_or(TileType::blocks_sight)
+}
+
+/// Compute every position visible from an origin within a radius.
+#[must_use]
+pub fn compute_fov(map: &Map, origin: IVec2, radius: i32) -> HashSet<IVec2> {
+    let mut visible = HashSet::new();
+    visible.insert(origin);
+
+    for octant in OCTANTS {
+        cast_light(map, origin, radius, 1, 1.0, 0.0, octant, &mut visible);
+    }
+
+    visible
+}
+
+/// Scan a single octant row by row, starting at `row`, between the `start` and `end` slope.
+/// Recurse into the next row whenever a blocking tile split the scanned area.
+#[allow(clippy::too_many_arguments)]
+fn cast_light(
+    map: &Map,
+    origin: IVec2,
+    radius: i32,
+    row: i32,
+    mut start: f32,
+    end: f32,
+    (xx, xy, yx, yy): (i32, i32, i32, i32),
+    visible: &mut HashSet<IVec2>,
+) {
+    if start < end {
+        return;
+    }
+
+    let mut new_start = 0.0;
+
+    for distance in row..=radius {
+        let dy = -distance;
+        let mut blocked = false;
+
+        for dx in -distance..=0 {
+            let position = origin + IVec2::new(dx * xx + dy * xy, dx * yx + dy * yy);
+
+            let left_slope = (dx as f32 - 0.5) / (dy as f32 + 0.5);
+            let right_slope = (dx as f32 + 0.5) / (dy as f32 - 0.5);
+
+            if start < right_slope {
+                continue;
+            }
+            if end > left_slope {
+                break;
+            }
+
+            if dx * dx + dy * dy <= radius * radius {
+                visible.insert(position);
+            }
+
+            let blocking = blocks_sight(map, position);
+
+            if blocked {
+                if blocking {
+                    new_start = right_slope;
+                } else {
+                    blocked = false;
+                    start = new_start;
+                }
+            } else if blocking && distance < radius {
+                blocked = true;
+                cast_light(
+                    map,
+                    origin,
+                    radius,
+                    distance + 1,
+                    start,
+                    left_slope,
+                    (xx, xy, yx, yy),
+                    visible,
+                );
+                new_start = right_slope;
+            }
+        }
+
+        if blocked {
+            break;
+        }
+    }
+}
+
+/// Check if `to` can be seen from `from` by walking a line between them. Only the position
+/// between the 2 end need to not block sight, so a wall can be seen.
+///
+/// The line is always walked from the same end, so swapping `from` and `to` give the same result.
+#[must_use]
+pub fn line_of_sight(map: &Map, from: IVec2, to: IVec2) -> bool {
+    let (from, to) = if (from.x, from.y) <= (to.x, to.y) {
+        (from, to)
+    } else {
+        (to, from)
+    };
+
+    let diff = (to - from).abs();
+    let step = (to - from).signum();
+
+    let mut position = from;
+    let mut error = diff.x - diff.y;
+
+    while position != to {
+        if position != from && blocks_sight(map, position) {
+            return false;
+        }
+
+        let double = error * 2;
+        if double > -diff.y {
+            error -= diff.y;
+            position.x += step.x;
+        }
+        if double < diff.x {
+            error += diff.x;
+            position.y += step.y;
+        }
+    }
+
+    true
+}
+
+/// System to recompute every [`Viewshed`] when its entity move or the [`Map`] change.
+pub fn update_viewshed(
+    mut map: ResMut<Map>,
+    mut viewers: Query<(Ref<GridTransform>, &mut Viewshed, Has<Player>)>,
+) {
+    let map_changed = map.is_changed();
+
+    for (transform, mut viewshed, is_player) in &mut viewers {
+        if !map_changed && !transform.is_changed() && !viewshed.is_added() {
+            continue;
+        }
+
+        viewshed.visible = compute_fov(&map, transform.translation, viewshed.radius);
+
+        if is_player {
+            // exploring only matter for rendering, do not make the whole map look changed
+            map.bypass_change_detection()
+                .explore(viewshed.visible.iter().copied());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::test_rooms::{empty_room, single_room};
+
+    #[test]
+    fn open_room_fully_visible() {
+        let map = single_room(&empty_room());
+        let visible = compute_fov(&map, IVec2::new(10, 6), 30);
+
+        // every tile of the room, including the border walls
+        for y in 0..i32::from(HEIGHT) {
+            for x in 0..i32::from(WIDTH) {
+                assert!(
+                    visible.contains(&IVec2::new(x, y)),
+                    "({x}, {y}) not visible"
+                );
+            }
+        }
+        // nothing past the border walls
+        assert!(!visible.contains(&IVec2::new(-1, 6)));
+        assert!(!visible.contains(&IVec2::new(i32::from(WIDTH), 6)));
+    }
+
+    #[test]
+    fn radius_limit_sight() {
+        let map = single_room(&empty_room());
+        let visible = compute_fov(&map, IVec2::new(10, 6), 2);
+
+        assert!(visible.contains(&IVec2::new(12, 6)));
+        assert!(!visible.contains(&IVec2::new(13, 6)));
+    }
+
+    #[test]
+    fn wall_block_corridor() {
+        let mut room = empty_room();
+        // horizontal corridor on row 6, closed by a wall at x = 8
+        for x in 1..(WIDTH - 1) {
+            room.set_tile(UVec2::new(x.into(), 5), TileType::Wall);
+            room.set_tile(UVec2::new(x.into(), 7), TileType::Wall);
+        }
+        room.set_tile(UVec2::new(8, 6), TileType::Wall);
+        let map = single_room(&room);
+
+        let visible = compute_fov(&map, IVec2::new(2, 6), 30);
+
+        assert!(visible.contains(&IVec2::new(7, 6)));
+        // the wall itself can be seen, but not what is behind it
+        assert!(visible.contains(&IVec2::new(8, 6)));
+        assert!(!visible.contains(&IVec2::new(9, 6)));
+        assert!(!visible.contains(&IVec2::new(15, 6)));
+
+        assert!(line_of_sight(&map, IVec2::new(2, 6), IVec2::new(8, 6)));
+        assert!(!line_of_sight(&map, IVec2::new(2, 6), IVec2::new(9, 6)));
+    }
+
+    #[test]
+    fn line_of_sight_symmetric() {
+        let mut room = empty_room();
+        for position in [(5, 3), (6, 3), (10, 8), (14, 4), (14, 5), (3, 9)] {
+            room.set_tile(UVec2::new(position.0, position.1), TileType::Wall);
+        }
+        let map = single_room(&room);
+
+        let positions: Vec<IVec2> = (1..i32::from(HEIGHT) - 1)
+            .flat_map(|y| (1..i32::from(WIDTH) - 1).map(move |x| IVec2::new(x, y)))
+            .filter(|p| map.get_tile(*p).is_some_and(TileType::is_walkable))
+            .collect();
+
+        for from in &positions {
+            for to in &positions {
+                assert_eq!(
+                    line_of_sight(&map, *from, *to),
+                    line_of_sight(&map, *to, *from),
+                    "{from} and {to}"
+                );
+            }
+        }
+    }
+}
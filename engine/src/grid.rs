@@ -17,15 +17,10 @@ pub struct GridTransform {
 }
 
 impl GridTransform {
-    /// Translate in a direction by some amount in place.
+    /// Translate in a direction by some amount in place. A diagonal direction move by `amount`
+    /// on both axis.
     pub fn translate_mut(&mut self, dir: Direction, amount: i32) {
-        match dir {
-            Direction::Zero => (),
-            Direction::Up => self.translation.y -= amount,
-            Direction::Left => self.translation.x -= amount,
-            Direction::Down => self.translation.y += amount,
-            Direction::Right => self.translation.x += amount,
-        }
+        self.translation += dir.offset() * amount;
     }
 
     /// Translate in a direction by some amount and return the new position. This does not change the current
@@ -34,17 +29,8 @@ impl GridTransform {
     /// For the in place version see [`translate_mut`](GridTransform::translate_mut)
     #[must_use = "Consider using `translate_mut` if you want to modify it in place"]
     pub fn translate(&self, dir: Direction, amount: i32) -> GridTransform {
-        let mut translation = self.translation;
-        match dir {
-            Direction::Zero => (),
-            Direction::Up => translation -= IVec2::Y * amount,
-            Direction::Left => translation -= IVec2::X * amount,
-            Direction::Down => translation += IVec2::Y * amount,
-            Direction::Right => translation += IVec2::X * amount,
-        }
-
         GridTransform {
-            translation,
+            translation: self.translation + dir.offset() * amount,
             ..*self
         }
     }
@@ -73,6 +59,23 @@ impl GridTransform {
         Vec3::new(x, y, 0.0)
     }
 
+    /// Create a new [`GridTransform`] at the tile under a world position, rounding to the closest
+    /// tile. Inverse of [`as_vec3`](GridTransform::as_vec3).
+    #[must_use]
+    pub fn from_world(position: Vec2) -> Self {
+        let f32_tile = f32::from(TILE_SIZE);
+        let f32_width = f32::from(WIDTH - 1);
+        let f32_height = f32::from(HEIGHT - 1);
+
+        let x = position.x / f32_tile + f32_width / 2.0;
+        let y = f32_height / 2.0 - position.y / f32_tile;
+
+        GridTransform {
+            translation: Vec2::new(x, y).round().as_ivec2(),
+            ..GridTransform::default()
+        }
+    }
+
     /// Convert this transform to a [`Vec3`] with the given `z` index.
     #[must_use]
     pub fn as_vec3_with_z(&self, z: f32) -> Vec3 {
@@ -88,3 +91,45 @@ pub fn update_transform(
         transform.translation = grid.as_vec3_with_z(transform.translation.z);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_world_inverse_of_as_vec3() {
+        for (x, y) in [
+            (0, 0),
+            (5, 3),
+            (i32::from(WIDTH) - 1, i32::from(HEIGHT) - 1),
+            (-1, -1),
+            (-7, 4),
+            (30, -25),
+            (-100, -100),
+        ] {
+            let grid = GridTransform::from_xy(x, y);
+            assert_eq!(
+                GridTransform::from_world(grid.as_vec3().xy()).translation,
+                grid.translation
+            );
+        }
+    }
+
+    #[test]
+    fn from_world_round_to_closest_tile() {
+        let center = GridTransform::from_xy(-3, 2).as_vec3().xy();
+        let almost_half = f32::from(TILE_SIZE) / 2.0 - 0.1;
+
+        for offset in [
+            Vec2::new(almost_half, 0.0),
+            Vec2::new(-almost_half, 0.0),
+            Vec2::new(0.0, almost_half),
+            Vec2::new(almost_half, -almost_half),
+        ] {
+            assert_eq!(
+                GridTransform::from_world(center + offset).translation,
+                IVec2::new(-3, 2)
+            );
+        }
+    }
+}
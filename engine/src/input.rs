@@ -0,0 +1,770 @@
+//! Input mapping from physical key and gamepad button to game [`Action`].
+//!
+//! The [`read_actions`] system send an [`ActionEvent`] for every pressed key or button bound in
+//! the [`InputBindings`] resource, game system should only read these events instead of the
+//! keyboard or gamepad. The gamepad left stick always move, snapped to a grid direction with the
+//! [`GamepadOptions`]. Holding an input bound to a repeatable action keep sending it at the rate of
+//! the [`KeyRepeat`] resource.
+//!
+//! Action taking a turn are also queued in the [`InputBuffer`] by the [`buffer_actions`] system, so
+//! game system that can not act right away, such as while a [`TransformAnimation`] is playing, can
+//! execute them once they are able to instead of dropping them. Other action are only sent as
+//! [`ActionEvent`] for their consumer to read directly.
+//!
+//! The bindings can be loaded from and saved to a config file in the user config directory, see
+//! [`InputConfig`]. The file is only written by [`save_bindings`] once the bindings change, with
+//! one action per line followed by every key and button bound to it:
+//! ```text
+//! # comment
+//! move_up KeyW ArrowUp Numpad8 GamepadDPadUp
+//! wait Space Numpad5 GamepadWest
+//! ```
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use thiserror::Error;
+
+use crate::prelude::*;
+
+/// Game action that can be bound to a key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Move a step in a direction.
+    Move(Direction),
+    /// Skip the turn.
+    Wait,
+    /// Interact with what is in front.
+    Interact,
+    /// Open the inventory.
+    Inventory,
+    /// Pause the game.
+    Pause,
+}
+
+impl Action {
+    /// Every action that can be bound.
+    pub const ALL: [Action; 12] = [
+        Action::Move(Direction::Up),
+        Action::Move(Direction::Down),
+        Action::Move(Direction::Left),
+        Action::Move(Direction::Right),
+        Action::Move(Direction::UpLeft),
+        Action::Move(Direction::UpRight),
+        Action::Move(Direction::DownLeft),
+        Action::Move(Direction::DownRight),
+        Action::Wait,
+        Action::Interact,
+        Action::Inventory,
+        Action::Pause,
+    ];
+
+    /// Name of the action in the config file.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Move(Direction::Zero) => "move",
+            Action::Move(Direction::Up) => "move_up",
+            Action::Move(Direction::Down) => "move_down",
+            Action::Move(Direction::Left) => "move_left",
+            Action::Move(Direction::Right) => "move_right",
+            Action::Move(Direction::UpLeft) => "move_up_left",
+            Action::Move(Direction::UpRight) => "move_up_right",
+            Action::Move(Direction::DownLeft) => "move_down_left",
+            Action::Move(Direction::DownRight) => "move_down_right",
+            Action::Wait => "wait",
+            Action::Interact => "interact",
+            Action::Inventory => "inventory",
+            Action::Pause => "pause",
+        }
+    }
+
+    /// Get an action from its name in the config file.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|a| a.name() == name)
+    }
+
+    /// Return if the action is repeated while its key is held.
+    #[must_use]
+    pub fn repeats(self) -> bool {
+        matches!(self, Action::Move(_))
+    }
+
+    /// Return if the action take the turn of the player, and so is queued in the [`InputBuffer`].
+    #[must_use]
+    pub fn takes_turn(self) -> bool {
+        matches!(self, Action::Move(_) | Action::Wait)
+    }
+}
+
+/// Event sent when a bound key is pressed, or repeated while held.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ActionEvent {
+    /// Action bound to the key.
+    pub action: Action,
+    /// If the event is a repeat of a held key.
+    pub repeat: bool,
+}
+
+/// Create the list of every key that can be used in the config file along with its name.
+macro_rules! named_keys {
+    ($($key:ident),* $(,)?) => {
+        /// Every key that can be used in the config file with its name.
+        const NAMED_KEYS: [(KeyCode, &str); ${count($key)}] = [$((KeyCode::$key, stringify!($key))),*];
+    };
+}
+
+#[rustfmt::skip]
+named_keys!(
+    KeyA, KeyB, KeyC, KeyD, KeyE, KeyF, KeyG, KeyH, KeyI, KeyJ, KeyK, KeyL, KeyM,
+    KeyN, KeyO, KeyP, KeyQ, KeyR, KeyS, KeyT, KeyU, KeyV, KeyW, KeyX, KeyY, KeyZ,
+    Digit0, Digit1, Digit2, Digit3, Digit4, Digit5, Digit6, Digit7, Digit8, Digit9,
+    Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+    NumpadAdd, NumpadSubtract, NumpadMultiply, NumpadDivide, NumpadDecimal, NumpadEnter,
+    ArrowUp, ArrowDown, ArrowLeft, ArrowRight,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    Space, Enter, Escape, Tab, Backspace, Delete, Insert, Home, End, PageUp, PageDown,
+    ShiftLeft, ShiftRight, ControlLeft, ControlRight, AltLeft, AltRight,
+    Comma, Period, Slash, Backslash, Semicolon, Quote, BracketLeft, BracketRight,
+    Minus, Equal, Backquote,
+);
+
+/// Every gamepad button that can be used in the config file with its name.
+const NAMED_BUTTONS: [(GamepadButton, &str); 19] = [
+    (GamepadButton::South, "GamepadSouth"),
+    (GamepadButton::East, "GamepadEast"),
+    (GamepadButton::North, "GamepadNorth"),
+    (GamepadButton::West, "GamepadWest"),
+    (GamepadButton::C, "GamepadC"),
+    (GamepadButton::Z, "GamepadZ"),
+    (GamepadButton::LeftTrigger, "GamepadLeftTrigger"),
+    (GamepadButton::LeftTrigger2, "GamepadLeftTrigger2"),
+    (GamepadButton::RightTrigger, "GamepadRightTrigger"),
+    (GamepadButton::RightTrigger2, "GamepadRightTrigger2"),
+    (GamepadButton::Select, "GamepadSelect"),
+    (GamepadButton::Start, "GamepadStart"),
+    (GamepadButton::Mode, "GamepadMode"),
+    (GamepadButton::LeftThumb, "GamepadLeftThumb"),
+    (GamepadButton::RightThumb, "GamepadRightThumb"),
+    (GamepadButton::DPadUp, "GamepadDPadUp"),
+    (GamepadButton::DPadDown, "GamepadDPadDown"),
+    (GamepadButton::DPadLeft, "GamepadDPadLeft"),
+    (GamepadButton::DPadRight, "GamepadDPadRight"),
+];
+
+/// Get the name of a key in the config file.
+fn key_name(key: KeyCode) -> Option<&'static str> {
+    NAMED_KEYS.iter().find(|(k, _)| *k == key).map(|(_, n)| *n)
+}
+
+/// Get a key from its name in the config file.
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    NAMED_KEYS.iter().find(|(_, n)| *n == name).map(|(k, _)| *k)
+}
+
+/// Get the name of a gamepad button in the config file.
+fn button_name(button: GamepadButton) -> Option<&'static str> {
+    NAMED_BUTTONS
+        .iter()
+        .find(|(b, _)| *b == button)
+        .map(|(_, n)| *n)
+}
+
+/// Get a gamepad button from its name in the config file.
+fn button_from_name(name: &str) -> Option<GamepadButton> {
+    NAMED_BUTTONS
+        .iter()
+        .find(|(_, n)| *n == name)
+        .map(|(b, _)| *b)
+}
+
+#[non_exhaustive]
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum InputConfigError {
+    #[error("Could not access input config: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid action in input config: {0}")]
+    Action(String),
+    #[error("Invalid key in input config: {0}")]
+    Key(String),
+}
+
+/// Resource holding the path of the bindings config file.
+#[derive(Resource, Clone, Debug)]
+pub struct InputConfig {
+    /// Path of the config file.
+    pub path: PathBuf,
+}
+
+impl InputConfig {
+    /// Create a new [`InputConfig`] using `bindings.cfg` in the config directory of the user for an
+    /// application. Return [`None`] if the config directory can not be found.
+    ///
+    /// The config directory is `$XDG_CONFIG_HOME`, then `$HOME/.config`, then `%APPDATA%`.
+    #[must_use]
+    pub fn user(application: &str) -> Option<Self> {
+        let env = |name| std::env::var_os(name).filter(|v| !v.is_empty());
+
+        let dir = env("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .or_else(|| env("APPDATA").map(PathBuf::from))?;
+
+        Some(InputConfig {
+            path: dir.join(application).join("bindings.cfg"),
+        })
+    }
+}
+
+/// Resource mapping key to [`Action`].
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct InputBindings {
+    keys: HashMap<KeyCode, Action>,
+    buttons: HashMap<GamepadButton, Action>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use GamepadButton as B;
+        use KeyCode as K;
+
+        let mut bindings = InputBindings::empty();
+
+        for (keys, action) in [
+            (
+                &[K::KeyW, K::ArrowUp, K::Numpad8][..],
+                Action::Move(Direction::Up),
+            ),
+            (
+                &[K::KeyS, K::ArrowDown, K::Numpad2],
+                Action::Move(Direction::Down),
+            ),
+            (
+                &[K::KeyA, K::ArrowLeft, K::Numpad4],
+                Action::Move(Direction::Left),
+            ),
+            (
+                &[K::KeyD, K::ArrowRight, K::Numpad6],
+                Action::Move(Direction::Right),
+            ),
+            (&[K::KeyQ, K::Numpad7], Action::Move(Direction::UpLeft)),
+            (&[K::KeyE, K::Numpad9], Action::Move(Direction::UpRight)),
+            (&[K::KeyZ, K::Numpad1], Action::Move(Direction::DownLeft)),
+            (&[K::KeyC, K::Numpad3], Action::Move(Direction::DownRight)),
+            (&[K::Space, K::Numpad5], Action::Wait),
+            (&[K::KeyF, K::Enter], Action::Interact),
+            (&[K::KeyI, K::Tab], Action::Inventory),
+            (&[K::Escape], Action::Pause),
+        ] {
+            for key in keys {
+                bindings.bind(*key, action);
+            }
+        }
+
+        for (button, action) in [
+            (B::DPadUp, Action::Move(Direction::Up)),
+            (B::DPadDown, Action::Move(Direction::Down)),
+            (B::DPadLeft, Action::Move(Direction::Left)),
+            (B::DPadRight, Action::Move(Direction::Right)),
+            (B::West, Action::Wait),
+            (B::South, Action::Interact),
+            (B::North, Action::Inventory),
+            (B::Start, Action::Pause),
+        ] {
+            bindings.bind_button(button, action);
+        }
+
+        bindings
+    }
+}
+
+impl InputBindings {
+    /// Create bindings without any key bound.
+    #[must_use]
+    pub fn empty() -> Self {
+        InputBindings {
+            keys: HashMap::new(),
+            buttons: HashMap::new(),
+        }
+    }
+
+    /// Bind a key to an action, replacing any action previously bound to it.
+    pub fn bind(&mut self, key: KeyCode, action: Action) {
+        self.keys.insert(key, action);
+    }
+
+    /// Remove the binding of a key.
+    pub fn unbind(&mut self, key: KeyCode) {
+        self.keys.remove(&key);
+    }
+
+    /// Get the action bound to a key.
+    #[must_use]
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        self.keys.get(&key).copied()
+    }
+
+    /// Bind a gamepad button to an action, replacing any action previously bound to it.
+    pub fn bind_button(&mut self, button: GamepadButton, action: Action) {
+        self.buttons.insert(button, action);
+    }
+
+    /// Remove the binding of a gamepad button.
+    pub fn unbind_button(&mut self, button: GamepadButton) {
+        self.buttons.remove(&button);
+    }
+
+    /// Get the action bound to a gamepad button.
+    #[must_use]
+    pub fn button_action(&self, button: GamepadButton) -> Option<Action> {
+        self.buttons.get(&button).copied()
+    }
+
+    /// Get every gamepad button bound to an action, in the same order as the config file.
+    #[must_use]
+    pub fn buttons_for(&self, action: Action) -> Vec<GamepadButton> {
+        let mut buttons: Vec<GamepadButton> = self
+            .buttons
+            .iter()
+            .filter(|(_, a)| **a == action)
+            .map(|(b, _)| *b)
+            .collect();
+        buttons.sort_by_key(|b| NAMED_BUTTONS.iter().position(|(n, _)| n == b));
+        buttons
+    }
+
+    /// Get every key bound to an action, in the same order as the config file.
+    #[must_use]
+    pub fn keys_for(&self, action: Action) -> Vec<KeyCode> {
+        let mut keys: Vec<KeyCode> = self
+            .keys
+            .iter()
+            .filter(|(_, a)| **a == action)
+            .map(|(k, _)| *k)
+            .collect();
+        keys.sort_by_key(|k| NAMED_KEYS.iter().position(|(n, _)| n == k));
+        keys
+    }
+
+    /// Parse bindings from the content of a config file.
+    ///
+    /// # Errors
+    /// Return an error if an action, key or button name is not valid.
+    pub fn parse(config: &str) -> Result<Self, InputConfigError> {
+        let mut bindings = InputBindings::empty();
+
+        for line in config.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut words = line.split_whitespace();
+            let name = words.next().unwrap_or_default();
+            let action = Action::from_name(name)
+                .ok_or_else(|| InputConfigError::Action(name.to_string()))?;
+
+            for word in words {
+                if let Some(button) = button_from_name(word) {
+                    bindings.bind_button(button, action);
+                    continue;
+                }
+
+                let key =
+                    key_from_name(word).ok_or_else(|| InputConfigError::Key(word.to_string()))?;
+                bindings.bind(key, action);
+            }
+        }
+
+        Ok(bindings)
+    }
+
+    /// Write the bindings in the config file format. Key and button that do not have a name are
+    /// skipped.
+    #[must_use]
+    pub fn to_config(&self) -> String {
+        let mut config = String::new();
+
+        for action in Action::ALL {
+            config.push_str(action.name());
+            let keys = self.keys_for(action).into_iter().filter_map(key_name);
+            let buttons = self.buttons_for(action).into_iter().filter_map(button_name);
+            for name in keys.chain(buttons) {
+                config.push(' ');
+                config.push_str(name);
+            }
+            config.push('\n');
+        }
+
+        config
+    }
+
+    /// Load the bindings from a config file. If the file does not exist, the default bindings are
+    /// returned without creating it.
+    ///
+    /// # Errors
+    /// Return an error if the file can not be read, or is not valid.
+    pub fn load_or_default(path: impl AsRef<Path>) -> Result<Self, InputConfigError> {
+        let path = path.as_ref();
+
+        if path.is_file() {
+            return InputBindings::parse(&fs::read_to_string(path)?);
+        }
+
+        Ok(InputBindings::default())
+    }
+
+    /// Save the bindings to a config file, creating its directory if needed.
+    ///
+    /// # Errors
+    /// Return an error if the file can not be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), InputConfigError> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, self.to_config())?;
+        Ok(())
+    }
+}
+
+/// Resource with the options for gamepad stick.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct GamepadOptions {
+    /// Stick position under this length are ignored.
+    pub deadzone: f32,
+    /// Allow the stick to move in diagonal.
+    pub diagonal: bool,
+}
+
+impl Default for GamepadOptions {
+    fn default() -> Self {
+        GamepadOptions {
+            deadzone: 0.5,
+            diagonal: true,
+        }
+    }
+}
+
+impl GamepadOptions {
+    /// Snap a stick position to the closest grid direction, or [`Direction::Zero`] inside the
+    /// deadzone.
+    #[must_use]
+    pub fn stick_direction(&self, stick: Vec2) -> Direction {
+        if stick.length() < self.deadzone {
+            return Direction::Zero;
+        }
+
+        // the stick y axis point up, the grid y axis point down
+        let stick = stick.normalize() * Vec2::new(1.0, -1.0);
+
+        // axis under the threshold are ignored
+        let sign = |v: f32, threshold: f32| i32::from(v > threshold) - i32::from(v < -threshold);
+
+        let offset = if self.diagonal {
+            // split in 8 sector of 45 degree each
+            let threshold = std::f32::consts::FRAC_PI_8.sin();
+            IVec2::new(sign(stick.x, threshold), sign(stick.y, threshold))
+        } else if stick.x.abs() > stick.y.abs() {
+            IVec2::new(sign(stick.x, 0.0), 0)
+        } else {
+            IVec2::new(0, sign(stick.y, 0.0))
+        };
+
+        Direction::from_offset(offset)
+    }
+}
+
+/// Input that can be held to repeat its action.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum HeldInput {
+    Key(KeyCode),
+    Button(Entity, GamepadButton),
+    Stick(Entity, Direction),
+}
+
+/// Resource with the timing used to repeat held key, gamepad button or stick.
+#[derive(Resource, Clone, Debug)]
+pub struct KeyRepeat {
+    /// Time a key need to be held before it start repeating.
+    pub delay: Duration,
+    /// Time between each repeat.
+    pub rate: Duration,
+    /// Input being held and the time until its next repeat.
+    held: Option<(HeldInput, Duration)>,
+    /// Last direction of the stick of each gamepad.
+    sticks: HashMap<Entity, Direction>,
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        KeyRepeat {
+            delay: Duration::from_millis(250),
+            rate: Duration::from_millis(120),
+            held: None,
+            sticks: HashMap::new(),
+        }
+    }
+}
+
+/// System to send an [`ActionEvent`] for every bound key or gamepad button pressed, and every time
+/// a gamepad left stick is pushed in a new direction. The last pressed input is repeated while it
+/// is held.
+pub fn read_actions(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<(Entity, &Gamepad)>,
+    bindings: Res<InputBindings>,
+    options: Res<GamepadOptions>,
+    time: Res<Time>,
+    mut repeat: ResMut<KeyRepeat>,
+    mut events: EventWriter<ActionEvent>,
+) {
+    let mut pressed = vec![];
+
+    for key in keyboard.get_just_pressed() {
+        if let Some(action) = bindings.action(*key) {
+            pressed.push((HeldInput::Key(*key), action));
+        }
+    }
+
+    for (entity, gamepad) in &gamepads {
+        for button in gamepad.get_just_pressed() {
+            if let Some(action) = bindings.button_action(*button) {
+                pressed.push((HeldInput::Button(entity, *button), action));
+            }
+        }
+
+        let direction = options.stick_direction(gamepad.left_stick());
+        let previous = repeat.sticks.insert(entity, direction);
+        if !direction.is_zero() && previous != Some(direction) {
+            pressed.push((HeldInput::Stick(entity, direction), Action::Move(direction)));
+        }
+    }
+
+    for (input, action) in pressed {
+        events.send(ActionEvent {
+            action,
+            repeat: false,
+        });
+
+        if action.repeats() {
+            repeat.held = Some((input, repeat.delay));
+        }
+    }
+
+    let KeyRepeat {
+        rate, held, sticks, ..
+    } = &mut *repeat;
+
+    let Some((input, remaining)) = held else {
+        return;
+    };
+
+    let action = match *input {
+        HeldInput::Key(key) => keyboard.pressed(key).then(|| bindings.action(key)),
+        HeldInput::Button(entity, button) => gamepads
+            .get(entity)
+            .is_ok_and(|(_, gamepad)| gamepad.pressed(button))
+            .then(|| bindings.button_action(button)),
+        HeldInput::Stick(entity, direction) => {
+            (sticks.get(&entity) == Some(&direction)).then_some(Some(Action::Move(direction)))
+        }
+    };
+
+    let Some(action) = action else {
+        *held = None;
+        return;
+    };
+    let Some(action) = action else {
+        return;
+    };
+
+    *remaining = remaining.saturating_sub(time.delta());
+    if remaining.is_zero() {
+        *remaining = *rate;
+        events.send(ActionEvent {
+            action,
+            repeat: true,
+        });
+    }
+}
+
+/// Resource queuing [`Action`] taking a turn until they can be executed.
+#[derive(Resource, Clone, Debug)]
+pub struct InputBuffer {
+    /// Maximum number of action in the queue, any action past it is dropped.
+    pub depth: usize,
+    queue: VecDeque<Action>,
+}
+
+impl Default for InputBuffer {
+    fn default() -> Self {
+        InputBuffer::new(1)
+    }
+}
+
+impl InputBuffer {
+    /// Create a new empty [`InputBuffer`] with a given depth.
+    #[must_use]
+    pub fn new(depth: usize) -> Self {
+        InputBuffer {
+            depth,
+            queue: VecDeque::with_capacity(depth),
+        }
+    }
+
+    /// Queue an action. Return `false` if the queue is full and the action was dropped.
+    pub fn push(&mut self, action: Action) -> bool {
+        if self.queue.len() >= self.depth {
+            return false;
+        }
+
+        self.queue.push_back(action);
+        true
+    }
+
+    /// Take the oldest action in the queue.
+    pub fn pop(&mut self) -> Option<Action> {
+        self.queue.pop_front()
+    }
+
+    /// Remove every action in the queue.
+    pub fn clear(&mut self) {
+        self.queue.clear();
+    }
+
+    /// Number of action in the queue.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Return if the queue is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+/// System to queue every [`ActionEvent`] that [`Action::takes_turn`] in the [`InputBuffer`].
+/// Repeated action are only queued when the buffer is empty, so holding a key does not fill it.
+pub fn buffer_actions(mut events: EventReader<ActionEvent>, mut buffer: ResMut<InputBuffer>) {
+    for ActionEvent { action, repeat } in events.read().copied() {
+        if !action.takes_turn() || (repeat && !buffer.is_empty()) {
+            continue;
+        }
+
+        buffer.push(action);
+    }
+}
+
+/// System to load the [`InputBindings`] from the [`InputConfig`] file at startup, keeping the
+/// default bindings if there is none.
+///
+/// If the file can not be loaded the [`InputConfig`] is removed, so [`save_bindings`] does not
+/// overwrite a file the user may want to fix.
+pub fn load_bindings(
+    mut commands: Commands,
+    config: Option<Res<InputConfig>>,
+    mut bindings: ResMut<InputBindings>,
+) {
+    let Some(config) = config else {
+        return;
+    };
+
+    match InputBindings::load_or_default(&config.path) {
+        // loading is not a change to save
+        Ok(loaded) => *bindings.bypass_change_detection() = loaded,
+        Err(e) => {
+            warn!(
+                "Using default key bindings, could not load {}: {e}",
+                config.path.display()
+            );
+            commands.remove_resource::<InputConfig>();
+        }
+    }
+}
+
+/// System to save the [`InputBindings`] to the [`InputConfig`] file whenever they change.
+pub fn save_bindings(bindings: Res<InputBindings>, config: Option<Res<InputConfig>>) {
+    let Some(config) = config else {
+        return;
+    };
+    if !bindings.is_changed() || bindings.is_added() {
+        return;
+    }
+
+    if let Err(e) = bindings.save(&config.path) {
+        warn!(
+            "Could not save key bindings to {}, {e}",
+            config.path.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_round_trip() {
+        let bindings = InputBindings::default();
+        let parsed = InputBindings::parse(&bindings.to_config()).unwrap();
+
+        assert_eq!(parsed, bindings);
+    }
+
+    #[test]
+    fn custom_config_round_trip() {
+        let mut bindings = InputBindings::empty();
+        bindings.bind(KeyCode::KeyJ, Action::Move(Direction::DownLeft));
+        bindings.bind(KeyCode::Space, Action::Wait);
+        bindings.bind_button(GamepadButton::South, Action::Interact);
+
+        let config = bindings.to_config();
+        assert!(config.contains("wait Space\n"));
+        assert_eq!(InputBindings::parse(&config).unwrap(), bindings);
+    }
+
+    #[test]
+    fn parse_skip_comment_and_reject_unknown() {
+        let bindings = InputBindings::parse("# comment\n\nwait Space\n").unwrap();
+        assert_eq!(bindings.action(KeyCode::Space), Some(Action::Wait));
+
+        assert!(matches!(
+            InputBindings::parse("fly Space"),
+            Err(InputConfigError::Action(_))
+        ));
+        assert!(matches!(
+            InputBindings::parse("wait NotAKey"),
+            Err(InputConfigError::Key(_))
+        ));
+    }
+
+    #[test]
+    fn save_and_load_file() {
+        let dir = std::env::temp_dir().join(format!("engine-bindings-{}", std::process::id()));
+        let path = dir.join("nested").join("bindings.cfg");
+
+        // a missing file give the default without creating it
+        assert_eq!(
+            InputBindings::load_or_default(&path).unwrap(),
+            InputBindings::default()
+        );
+        assert!(!path.exists());
+
+        let mut bindings = InputBindings::default();
+        bindings.bind(KeyCode::KeyQ, Action::Pause);
+        bindings.save(&path).unwrap();
+        let loaded = InputBindings::load_or_default(&path);
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded.unwrap(), bindings);
+    }
+}
@@ -4,16 +4,27 @@
 //! Contain implementation for various engine functions and components.
 
 use bevy::ecs::system::{Resource, SystemId};
+use bevy::math::IVec2;
 use bevy::utils::HashMap;
+use thiserror::Error;
 
 pub mod prelude;
 
 pub mod animation;
 pub mod atlas;
+pub mod distance_map;
+pub mod facing;
+pub mod fov;
 pub mod grid;
+pub mod input;
 pub mod map;
+pub mod movement;
+pub mod pathfinding;
 pub mod player;
+pub mod pointer;
 pub mod render;
+pub mod spatial;
+pub mod turn;
 
 /// Size of each tile.
 pub static TILE_SIZE: u16 = 8;
@@ -27,9 +38,12 @@ pub static HEIGHT: u16 = 13;
 #[derive(Resource)]
 pub struct OneShotSystems(pub HashMap<String, SystemId>);
 
-/// Enum for direction.
+/// Enum for direction on the grid, either one of the 4 cardinal direction or one of the 4
+/// diagonal.
+///
+/// Up is toward negative `y`, the same as the grid used by [`GridTransform`](grid::GridTransform).
 #[allow(missing_docs)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Direction {
     /// Zero or no direction.
     Zero,
@@ -37,14 +51,157 @@ pub enum Direction {
     Left,
     Down,
     Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
 }
 
 impl Direction {
+    /// The 4 cardinal direction.
+    pub const CARDINAL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Left,
+        Direction::Down,
+        Direction::Right,
+    ];
+
+    /// The 4 diagonal direction.
+    pub const DIAGONAL: [Direction; 4] = [
+        Direction::UpLeft,
+        Direction::UpRight,
+        Direction::DownLeft,
+        Direction::DownRight,
+    ];
+
+    /// Every direction except [`Direction::Zero`].
+    pub const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::Left,
+        Direction::Down,
+        Direction::Right,
+        Direction::UpLeft,
+        Direction::UpRight,
+        Direction::DownLeft,
+        Direction::DownRight,
+    ];
+
     /// Return `true` is the direction is [`Direction::Zero`].
     #[must_use]
     pub fn is_zero(self) -> bool {
         matches!(self, Direction::Zero)
     }
+
+    /// Return `true` is the direction is one of the 4 diagonal.
+    #[must_use]
+    pub fn is_diagonal(self) -> bool {
+        Direction::DIAGONAL.contains(&self)
+    }
+
+    /// Grid offset of a single step in this direction.
+    #[must_use]
+    pub fn offset(self) -> IVec2 {
+        match self {
+            Direction::Zero => IVec2::ZERO,
+            Direction::Up => IVec2::NEG_Y,
+            Direction::Left => IVec2::NEG_X,
+            Direction::Down => IVec2::Y,
+            Direction::Right => IVec2::X,
+            Direction::UpLeft => IVec2::new(-1, -1),
+            Direction::UpRight => IVec2::new(1, -1),
+            Direction::DownLeft => IVec2::new(-1, 1),
+            Direction::DownRight => IVec2::new(1, 1),
+        }
+    }
+
+    /// Get the direction closest to an offset, only considering the sign of each axis.
+    #[must_use]
+    pub fn from_offset(offset: IVec2) -> Direction {
+        match (offset.x.signum(), offset.y.signum()) {
+            (0, -1) => Direction::Up,
+            (-1, 0) => Direction::Left,
+            (0, 1) => Direction::Down,
+            (1, 0) => Direction::Right,
+            (-1, -1) => Direction::UpLeft,
+            (1, -1) => Direction::UpRight,
+            (-1, 1) => Direction::DownLeft,
+            (1, 1) => Direction::DownRight,
+            _ => Direction::Zero,
+        }
+    }
+
+    /// Split a diagonal direction into its horizontal and vertical part. Return [`None`] for any
+    /// other direction.
+    #[must_use]
+    pub fn split(self) -> Option<(Direction, Direction)> {
+        let offset = self.offset();
+        self.is_diagonal().then(|| {
+            (
+                Direction::from_offset(offset.with_y(0)),
+                Direction::from_offset(offset.with_x(0)),
+            )
+        })
+    }
+
+    /// Convert to a [`CompassDir`], with north being [`Direction::Up`]. Return [`None`] for
+    /// [`Direction::Zero`].
+    #[must_use]
+    pub fn compass(self) -> Option<CompassDir> {
+        Some(match self {
+            Direction::Zero => return None,
+            Direction::Up => CompassDir::North,
+            Direction::Left => CompassDir::West,
+            Direction::Down => CompassDir::South,
+            Direction::Right => CompassDir::East,
+            Direction::UpLeft => CompassDir::NorthWest,
+            Direction::UpRight => CompassDir::NorthEast,
+            Direction::DownLeft => CompassDir::SouthWest,
+            Direction::DownRight => CompassDir::SouthEast,
+        })
+    }
+}
+
+impl From<Direction> for IVec2 {
+    fn from(dir: Direction) -> Self {
+        dir.offset()
+    }
+}
+
+#[non_exhaustive]
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum DirectionError {
+    #[error("Offset is not a single step: {0}")]
+    NotUnit(IVec2),
+}
+
+/// Convert an offset of a single step, each axis being `-1`, `0` or `1`. For any other offset use
+/// [`Direction::from_offset`].
+impl TryFrom<IVec2> for Direction {
+    type Error = DirectionError;
+
+    fn try_from(offset: IVec2) -> Result<Self, Self::Error> {
+        if offset.abs().max_element() > 1 {
+            return Err(DirectionError::NotUnit(offset));
+        }
+
+        Ok(Direction::from_offset(offset))
+    }
+}
+
+impl From<CompassDir> for Direction {
+    fn from(dir: CompassDir) -> Self {
+        match dir {
+            CompassDir::North => Direction::Up,
+            CompassDir::East => Direction::Right,
+            CompassDir::South => Direction::Down,
+            CompassDir::West => Direction::Left,
+            CompassDir::NorthEast => Direction::UpRight,
+            CompassDir::SouthEast => Direction::DownRight,
+            CompassDir::SouthWest => Direction::DownLeft,
+            CompassDir::NorthWest => Direction::UpLeft,
+        }
+    }
 }
 
 /// Compass type
@@ -71,6 +228,19 @@ pub struct QuadCompass<T> {
     pub west: T,
 }
 
+impl<T> QuadCompass<T> {
+    /// Get a mutable reference to the value in a direction. Return [`None`] for diagonal.
+    pub fn get_mut(&mut self, dir: CompassDir) -> Option<&mut T> {
+        match dir {
+            CompassDir::North => Some(&mut self.north),
+            CompassDir::East => Some(&mut self.east),
+            CompassDir::South => Some(&mut self.south),
+            CompassDir::West => Some(&mut self.west),
+            _ => None,
+        }
+    }
+}
+
 impl<T> From<OctCompass<T>> for QuadCompass<T> {
     fn from(
         OctCompass {
@@ -117,7 +287,7 @@ where
 
 /// Enum containing the directions of the compass.
 #[allow(missing_docs)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CompassDir {
     North,
     East,
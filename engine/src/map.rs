@@ -3,22 +3,29 @@
 //! The engine hold a glocal resource [`Map`] that hold the current loaded room as
 //! Currently each tile can only be a ground or wall tile.
 //!
-//! Wall tile are created using 4 sub wall tile, 2 for the top half and 2 for the bottom half. Each
-//! sub tile get choosen by considering the 2 adjacent tile to it, above and left for the top left sub
-//! tile, above and right for the top right sub tile, etc. The also consider the corner if all
-//! their adjacent tile are wall.
+//! Connected tile such as wall are auto tiled using the [`TileSet`] of their [`TileSetKind`]. The
+//! tile set pick the sprite for a tile by considering its neighbour with an [`AutoTileRule`]. The
+//! wall tile set use [`AutoTileRule::SubTile`], where each tile is made of 4 sub tile, 2 for the
+//! top half and 2 for the bottom half.
+//!
+//! Tile can be changed at runtime by sending a [`SetTile`] event, which update the stored layout
+//! and respawn the tile entities that depend on it.
+//!
+//! Every spawned tile is a child of its room [`RoomRoot`] entity, so a room can be despawned or
+//! rebuilt on its own using [`Map::despawn_room`] and [`Map::spawn_room`]. Only the rooms near
+//! [`Map::curr_room_pos`] are kept spawned by the [`stream_rooms`] system.
 
 use bevy::asset::LoadedFolder;
 use bevy::math::bool;
 use bevy::prelude::*;
-use bevy::utils::hashbrown::HashMap;
+use bevy::utils::hashbrown::{HashMap, HashSet};
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 
 use crate::prelude::*;
 
-mod wall;
-pub(crate) use wall::*;
+mod autotile;
+pub use autotile::*;
 
 mod generator;
 pub use generator::*;
@@ -26,6 +33,12 @@ pub use generator::*;
 mod asset;
 pub use asset::*;
 
+mod tile;
+pub use tile::*;
+
+mod room;
+pub use room::*;
+
 type NeighbourTile = OctCompass<bool>;
 
 /// Marker component for a sub tile.
@@ -65,22 +78,213 @@ pub struct Map {
     pub curr_room_pos: (i32, i32),
     /// Hashmap of room in the map.
     pub rooms: HashMap<(i32, i32), RoomLayout>,
+    /// Hashmap of spawned tile entity by their global grid position.
+    tiles: HashMap<IVec2, Entity>,
+    /// Hashmap of spawned room root entity.
+    room_entities: HashMap<(i32, i32), Entity>,
+    /// Global grid position that have been seen by the player.
+    explored: HashSet<IVec2>,
 }
 
 impl Map {
+    /// Create a new [`Map`] from a list of room layout, with the current room at `(0, 0)`.
+    #[must_use]
+    pub fn new(rooms: HashMap<(i32, i32), RoomLayout>) -> Self {
+        Map {
+            curr_room_pos: (0, 0),
+            rooms,
+            tiles: HashMap::new(),
+            room_entities: HashMap::new(),
+            explored: HashSet::new(),
+        }
+    }
+
+    /// Return if a global grid position have been seen by the player.
+    #[must_use]
+    pub fn is_explored(&self, position: IVec2) -> bool {
+        self.explored.contains(&position)
+    }
+
+    /// Mark global grid positions as seen by the player.
+    pub fn explore(&mut self, positions: impl IntoIterator<Item = IVec2>) {
+        self.explored.extend(positions);
+    }
+
     /// Get the current room layout.
     #[must_use]
     pub fn curr_room(&self) -> Option<&RoomLayout> {
         self.rooms.get(&self.curr_room_pos)
     }
+
+    /// Get the position of the room containing a global grid position.
+    #[must_use]
+    pub fn room_pos(position: IVec2) -> (i32, i32) {
+        let room = position.div_euclid(IVec2::new(WIDTH.into(), HEIGHT.into()));
+        (room.x, room.y)
+    }
+
+    /// Get the position within its room of a global grid position.
+    #[must_use]
+    pub fn local_pos(position: IVec2) -> UVec2 {
+        position
+            .rem_euclid(IVec2::new(WIDTH.into(), HEIGHT.into()))
+            .as_uvec2()
+    }
+
+    /// Convert a room position and a position within that room to a global grid position.
+    #[must_use]
+    pub fn room_to_world(room: (i32, i32), local: UVec2) -> IVec2 {
+        IVec2::new(room.0 * i32::from(WIDTH), room.1 * i32::from(HEIGHT)) + local.as_ivec2()
+    }
+
+    /// Get the room layout containing a global grid position.
+    #[must_use]
+    pub fn get_room(&self, position: IVec2) -> Option<&RoomLayout> {
+        self.rooms.get(&Map::room_pos(position))
+    }
+
+    /// Get the tile at a global grid position. Return [`None`] if there is no room there.
+    #[must_use]
+    pub fn get_tile(&self, position: IVec2) -> Option<TileType> {
+        self.get_room(position)
+            .map(|layout| layout.get_tile(Map::local_pos(position)))
+    }
+
+    /// Iterate over every tile in a rectangle of global grid position, inclusive of both corner.
+    /// Position without a room are skipped.
+    pub fn tiles_in_rect(&self, rect: IRect) -> impl Iterator<Item = (IVec2, TileType)> + '_ {
+        (rect.min.y..=rect.max.y)
+            .flat_map(move |y| (rect.min.x..=rect.max.x).map(move |x| IVec2::new(x, y)))
+            .filter_map(|position| Some((position, self.get_tile(position)?)))
+    }
+
+    /// Get neighbouring tile that connect to the given [`TileSetKind`] at a global grid position.
+    ///
+    /// Unlike [`RoomLayout::get_neighbour_tile`] this look into the adjacent room so tile connect
+    /// across room border. Position without a room are treated as wall.
+    #[must_use]
+    pub fn get_neighbour_tile(&self, kind: TileSetKind, position: IVec2) -> NeighbourTile {
+        let connects = |offset: IVec2| {
+            kind.connects(self.get_tile(position + offset).unwrap_or(TileType::Wall))
+        };
+
+        OctCompass {
+            north: connects(IVec2::NEG_Y),
+            east: connects(IVec2::X),
+            south: connects(IVec2::Y),
+            west: connects(IVec2::NEG_X),
+
+            north_east: connects(IVec2::NEG_Y + IVec2::X),
+            south_east: connects(IVec2::ONE),
+            south_west: connects(IVec2::Y + IVec2::NEG_X),
+            north_west: connects(IVec2::NEG_ONE),
+        }
+    }
 }
 
 /// Insert the resource for the global [`Map`]
 pub fn setup_tile_map(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.insert_resource(RoomList(asset_server.load_folder("rooms")));
+    commands.insert_resource(TileSets::load(&asset_server));
     commands.insert_resource(Visited(HashMap::new()));
-    commands.insert_resource(Map {
-        curr_room_pos: (0, 0),
-        rooms: HashMap::new(),
-    });
+    commands.insert_resource(Map::new(HashMap::new()));
+}
+
+/// Room helper shared by the test of every module working on the [`Map`].
+#[cfg(test)]
+pub(crate) mod test_rooms {
+    use super::*;
+
+    /// Create a room with wall around the border and ground everywhere else.
+    pub(crate) fn empty_room() -> RoomLayout {
+        let mut layout = [[TileType::Ground; WIDTH as usize]; HEIGHT as usize];
+        for (y, row) in layout.iter_mut().enumerate() {
+            for (x, tile) in row.iter_mut().enumerate() {
+                if x == 0 || y == 0 || x == (WIDTH - 1) as usize || y == (HEIGHT - 1) as usize {
+                    *tile = TileType::Wall;
+                }
+            }
+        }
+
+        RoomLayout {
+            doors: QuadCompass::default(),
+            layout,
+        }
+    }
+
+    /// Create a map with a single room at `(0, 0)`.
+    pub(crate) fn single_room(room: &RoomLayout) -> Map {
+        Map::new([((0, 0), *room)].into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn width() -> i32 {
+        i32::from(WIDTH)
+    }
+
+    fn height() -> i32 {
+        i32::from(HEIGHT)
+    }
+
+    #[test]
+    fn room_pos_at_origin_boundary() {
+        assert_eq!(Map::room_pos(IVec2::ZERO), (0, 0));
+        assert_eq!(Map::room_pos(IVec2::new(-1, 0)), (-1, 0));
+        assert_eq!(Map::room_pos(IVec2::new(0, -1)), (0, -1));
+        assert_eq!(Map::room_pos(IVec2::new(-1, -1)), (-1, -1));
+    }
+
+    #[test]
+    fn room_pos_on_negative_room_edge() {
+        // first and last tile of the room at (-1, -1)
+        assert_eq!(Map::room_pos(IVec2::new(-width(), -height())), (-1, -1));
+        assert_eq!(
+            Map::room_pos(IVec2::new(-width() - 1, -height() - 1)),
+            (-2, -2)
+        );
+        assert_eq!(Map::room_pos(IVec2::new(width() - 1, -1)), (0, -1));
+    }
+
+    #[test]
+    fn local_pos_negative() {
+        let last = UVec2::new(u32::from(WIDTH) - 1, u32::from(HEIGHT) - 1);
+
+        assert_eq!(Map::local_pos(IVec2::new(-1, -1)), last);
+        assert_eq!(Map::local_pos(IVec2::new(-width(), -height())), UVec2::ZERO);
+        assert_eq!(Map::local_pos(IVec2::new(-width() - 1, -1)), last);
+        assert_eq!(Map::local_pos(IVec2::new(-1, 0)), UVec2::new(last.x, 0));
+    }
+
+    #[test]
+    fn room_to_world_round_trip() {
+        for position in [
+            IVec2::ZERO,
+            IVec2::new(-1, -1),
+            IVec2::new(-width(), -height()),
+            IVec2::new(-width() - 1, height()),
+            IVec2::new(width() - 1, -height() + 1),
+            IVec2::new(-3 * width() + 5, -2 * height() + 7),
+        ] {
+            assert_eq!(
+                Map::room_to_world(Map::room_pos(position), Map::local_pos(position)),
+                position
+            );
+        }
+    }
+
+    #[test]
+    fn room_to_world_negative_room() {
+        assert_eq!(
+            Map::room_to_world((-1, -1), UVec2::ZERO),
+            IVec2::new(-width(), -height())
+        );
+        assert_eq!(
+            Map::room_to_world((-1, 0), UVec2::new(u32::from(WIDTH) - 1, 0)),
+            IVec2::new(-1, 0)
+        );
+    }
 }
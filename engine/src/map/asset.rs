@@ -32,6 +32,46 @@ impl TileType {
     pub fn is_wall(self) -> bool {
         matches!(self, TileType::Wall)
     }
+
+    /// Return if the tile can be walked on.
+    #[must_use]
+    pub fn is_walkable(self) -> bool {
+        matches!(self, TileType::Ground | TileType::Door(_))
+    }
+
+    /// Return if the tile block line of sight.
+    #[must_use]
+    pub fn blocks_sight(self) -> bool {
+        matches!(self, TileType::Wall)
+    }
+
+    /// Return if the tile can be placed, a door can only face a cardinal direction.
+    #[must_use]
+    pub fn is_valid(self) -> bool {
+        match self {
+            TileType::Door(dir) => !Direction::from(dir).is_diagonal(),
+            _ => true,
+        }
+    }
+
+    /// Name of the tile shown to the player.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            TileType::Wall => "Wall",
+            TileType::Ground => "Ground",
+            TileType::Door(_) => "Door",
+        }
+    }
+
+    /// Return the [`TileSetKind`] use to auto tile this tile, or [`None`] if it is not auto tiled.
+    #[must_use]
+    pub fn tile_set(self) -> Option<TileSetKind> {
+        match self {
+            TileType::Wall => Some(TileSetKind::Wall),
+            _ => None,
+        }
+    }
 }
 
 /// Asset for a room layout to be load by the engine.
@@ -168,34 +208,70 @@ impl RoomLayout {
             .unwrap_or(&TileType::Wall)
     }
 
-    fn get_wall_status(&self, position: UVec2, shortcut: bool, offset: IVec2) -> bool {
-        matches!(
+    /// Set a tile at position, keeping [`RoomLayout::doors`] up to date when a door is set or
+    /// cleared. Do nothing if an invalid position was given.
+    pub fn set_tile(&mut self, position: UVec2, tile: TileType) {
+        let Some(t) = self
+            .layout
+            .get_mut(position.y as usize)
+            .and_then(|v| v.get_mut(position.x as usize))
+        else {
+            return;
+        };
+
+        if let TileType::Door(dir) = *t {
+            if let Some(door) = self.doors.get_mut(dir) {
+                *door = false;
+            }
+        }
+        if let TileType::Door(dir) = tile {
+            if let Some(door) = self.doors.get_mut(dir) {
+                *door = true;
+            }
+        }
+
+        *t = tile;
+    }
+
+    fn get_connect_status(
+        &self,
+        kind: TileSetKind,
+        position: UVec2,
+        shortcut: bool,
+        offset: IVec2,
+    ) -> bool {
+        kind.connects(
             shortcut
                 .then_some(TileType::Wall)
                 .unwrap_or_else(|| self.get_tile((position.as_ivec2() + offset).as_uvec2())),
-            TileType::Wall | TileType::Door(_)
         )
     }
 
     /// Get neighbouring wall tile.
     #[must_use]
-    #[rustfmt::skip] // the formatting making it a bit worst imo
     pub fn get_neighbour_wall(&self, position: UVec2) -> NeighbourTile {
+        self.get_neighbour_tile(TileSetKind::Wall, position)
+    }
+
+    /// Get neighbouring tile that connect to the given [`TileSetKind`].
+    #[must_use]
+    #[rustfmt::skip] // the formatting making it a bit worst imo
+    pub fn get_neighbour_tile(&self, kind: TileSetKind, position: UVec2) -> NeighbourTile {
         let is_top = position.y == 0;
         let is_left = position.x == 0;
         let is_bottom = position.y == HEIGHT.into();
         let is_right = position.x == WIDTH.into();
 
         OctCompass {
-            north: self.get_wall_status(position, is_top, IVec2::NEG_Y),
-            east: self.get_wall_status(position, is_right, IVec2::X),
-            south: self.get_wall_status(position, is_bottom, IVec2::Y),
-            west: self.get_wall_status(position, is_left, IVec2::NEG_X),
-
-            north_east: self.get_wall_status(position, is_top && is_right, IVec2::NEG_Y + IVec2::X),
-            south_east: self.get_wall_status(position, is_bottom && is_right, IVec2::ONE),
-            south_west: self.get_wall_status( position, is_bottom && is_left, IVec2::Y + IVec2::NEG_X,),
-            north_west: self.get_wall_status(position, is_top && is_left, IVec2::NEG_ONE),
+            north: self.get_connect_status(kind, position, is_top, IVec2::NEG_Y),
+            east: self.get_connect_status(kind, position, is_right, IVec2::X),
+            south: self.get_connect_status(kind, position, is_bottom, IVec2::Y),
+            west: self.get_connect_status(kind, position, is_left, IVec2::NEG_X),
+
+            north_east: self.get_connect_status(kind, position, is_top && is_right, IVec2::NEG_Y + IVec2::X),
+            south_east: self.get_connect_status(kind, position, is_bottom && is_right, IVec2::ONE),
+            south_west: self.get_connect_status(kind, position, is_bottom && is_left, IVec2::Y + IVec2::NEG_X),
+            north_west: self.get_connect_status(kind, position, is_top && is_left, IVec2::NEG_ONE),
         }
     }
 }
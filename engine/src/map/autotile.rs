@@ -0,0 +1,467 @@
+//! Auto tiling rules for connected tile.
+//!
+//! Each connected [`TileType`] belong to a [`TileSetKind`], which is paired with a [`TileSet`]
+//! asset loaded from `tilesets/<name>.tileset`. The tile set describe which [`AutoTileRule`] is
+//! used to pick the variant from the [`NeighbourTile`] and which sprite in the atlas each variant
+//! use.
+//!
+//! The tile set file is a list of `key value` lines:
+//! ```text
+//! rule sub_tile
+//! variants 0 1 2 3 4 5 6 7 8 9
+//! ```
+//! `variants` is optional and default to each variant using the sprite with the same index.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+    utils::HashMap,
+};
+use thiserror::Error;
+
+use crate::prelude::*;
+
+use super::{NeighbourTile, SubTile};
+
+/// Kind of tile set, each connected [`TileType`] use one of these.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TileSetKind {
+    /// Wall tile set, connect to wall and door tile.
+    Wall,
+}
+
+impl TileSetKind {
+    /// Every tile set kind.
+    pub const ALL: [TileSetKind; 1] = [TileSetKind::Wall];
+
+    /// Name of the tile set, use for both the tile set file and the atlas name.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            TileSetKind::Wall => "wall",
+        }
+    }
+
+    /// Return if a tile count as connected for this tile set.
+    #[must_use]
+    pub fn connects(self, tile: TileType) -> bool {
+        match self {
+            TileSetKind::Wall => matches!(tile, TileType::Wall | TileType::Door(_)),
+        }
+    }
+}
+
+/// Rule use to pick the variant of a tile from its neighbour.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AutoTileRule {
+    /// The tile is made of 4 sub tile, 2 for the top half and 2 for the bottom half. Each sub tile
+    /// consider the 2 adjacent tile to it and the corner between them if both are connected.
+    ///
+    /// Have 10 variants, 5 for the top sub tile then 5 for the bottom sub tile, each ordered:
+    /// both side, vertical side only, horizontal side only, no side, both side and the corner.
+    /// The sprite are for the right sub tile and are flipped for the left sub tile.
+    SubTile,
+    /// 16 tile marching square, only consider the 4 cardinal neighbour.
+    ///
+    /// The variant is a bit mask of the connected side: north `1`, east `2`, south `4`, west `8`.
+    Cardinal,
+    /// 47 tile blob, consider all 8 neighbour, but a corner only count if both adjacent side are
+    /// connected.
+    ///
+    /// The variant is the position of the neighbour bit mask in the list of every valid mask, in
+    /// ascending order. The mask bit go clockwise from north `1` to north west `128`.
+    Blob,
+}
+
+/// List of every valid [`AutoTileRule::Blob`] mask in ascending order.
+const BLOB_MASKS: [u8; 47] = {
+    let mut masks = [0; 47];
+    let mut len = 0;
+    let mut mask = 0;
+
+    while mask <= u8::MAX as usize {
+        #[allow(clippy::cast_possible_truncation)]
+        if blob_mask(mask as u8) == mask as u8 {
+            masks[len] = mask as u8;
+            len += 1;
+        }
+        mask += 1;
+    }
+
+    masks
+};
+
+/// Remove any corner bit from a neighbour mask that do not have both adjacent side connected.
+const fn blob_mask(mask: u8) -> u8 {
+    let mut result = mask & 0b0101_0101; // only the side
+
+    // each corner is between the side before and after it
+    let mut corner = 1;
+    while corner < 8 {
+        let before = 1 << (corner - 1);
+        let after = 1 << ((corner + 1) % 8);
+        if mask & before != 0 && mask & after != 0 {
+            result |= mask & (1 << corner);
+        }
+        corner += 2;
+    }
+
+    result
+}
+
+impl AutoTileRule {
+    /// Number of variant this rule can pick from.
+    #[must_use]
+    pub fn variant_count(self) -> usize {
+        match self {
+            AutoTileRule::SubTile => 10,
+            AutoTileRule::Cardinal => 16,
+            AutoTileRule::Blob => BLOB_MASKS.len(),
+        }
+    }
+
+    /// Pick the variant of the sub tile for [`AutoTileRule::SubTile`].
+    fn sub_tile_variant(top: bool, left: bool, neighbour: NeighbourTile) -> usize {
+        let vert_wall = if top {
+            neighbour.north
+        } else {
+            neighbour.south
+        };
+        let horz_wall = if left { neighbour.west } else { neighbour.east };
+
+        let corner = match (top, left) {
+            (true, true) => neighbour.north_west,
+            (true, false) => neighbour.north_east,
+            (false, true) => neighbour.south_west,
+            (false, false) => neighbour.south_east,
+        };
+
+        let top_offset = if top { 0 } else { 5 };
+
+        if corner && horz_wall && vert_wall {
+            return top_offset + 4;
+        }
+
+        let horz_offset = if horz_wall { 0 } else { 2 };
+        let vert_offset = if vert_wall { 0 } else { 1 };
+
+        top_offset + horz_offset + vert_offset
+    }
+
+    /// Pick the variant for [`AutoTileRule::Cardinal`].
+    fn cardinal_variant(neighbour: NeighbourTile) -> usize {
+        [
+            neighbour.north,
+            neighbour.east,
+            neighbour.south,
+            neighbour.west,
+        ]
+        .into_iter()
+        .zip(0..)
+        .map(|(connected, bit)| usize::from(connected) << bit)
+        .sum()
+    }
+
+    /// Pick the variant for [`AutoTileRule::Blob`].
+    fn blob_variant(neighbour: NeighbourTile) -> usize {
+        #[rustfmt::skip]
+        let mask = [
+            neighbour.north, neighbour.north_east, neighbour.east, neighbour.south_east,
+            neighbour.south, neighbour.south_west, neighbour.west, neighbour.north_west,
+        ]
+        .into_iter()
+        .zip(0..)
+        .fold(0, |mask, (connected, bit)| mask | (u8::from(connected) << bit));
+
+        BLOB_MASKS
+            .iter()
+            .position(|m| *m == blob_mask(mask))
+            .unwrap()
+    }
+}
+
+/// Asset for a tile set, the data driving the auto tiling of a [`TileSetKind`].
+#[derive(Asset, TypePath, Clone, Debug)]
+pub struct TileSet {
+    /// Rule use to pick the variant.
+    pub rule: AutoTileRule,
+    /// Sprite index in the atlas for each variant.
+    pub variants: Vec<usize>,
+}
+
+impl TileSet {
+    /// Create the sprite pieces for a tile of this tile set given its neighbour.
+    pub(crate) fn pieces(&self, kind: TileSetKind, neighbour: NeighbourTile) -> Vec<TilePiece> {
+        let texture = |variant: usize| Texture::Tile {
+            set: kind,
+            index: self.variants[variant],
+        };
+
+        match self.rule {
+            AutoTileRule::SubTile => [(true, true), (true, false), (false, true), (false, false)]
+                .into_iter()
+                .map(|(top, left)| {
+                    let x = if left { -2.0 } else { 2.0 };
+                    let y = if top { 2.5 } else { -1.5 };
+
+                    TilePiece::new(
+                        texture(AutoTileRule::sub_tile_variant(top, left, neighbour)),
+                        Vec2::new(x, y),
+                        left,
+                    )
+                })
+                .collect(),
+            AutoTileRule::Cardinal => vec![TilePiece::new(
+                texture(AutoTileRule::cardinal_variant(neighbour)),
+                Vec2::ZERO,
+                false,
+            )],
+            AutoTileRule::Blob => vec![TilePiece::new(
+                texture(AutoTileRule::blob_variant(neighbour)),
+                Vec2::ZERO,
+                false,
+            )],
+        }
+    }
+}
+
+/// Resource holding the [`TileSet`] of every [`TileSetKind`].
+#[derive(Resource, Debug)]
+pub struct TileSets(pub HashMap<TileSetKind, Handle<TileSet>>);
+
+impl TileSets {
+    /// Load the tile set of every [`TileSetKind`].
+    #[must_use]
+    pub fn load(asset_server: &AssetServer) -> Self {
+        TileSets(
+            TileSetKind::ALL
+                .into_iter()
+                .map(|kind| {
+                    (
+                        kind,
+                        asset_server.load(format!("tilesets/{}.tileset", kind.name())),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// Get the tile set of a kind. Return [`None`] if it is not loaded yet.
+    #[must_use]
+    pub fn get<'a>(
+        &self,
+        kind: TileSetKind,
+        tile_sets: &'a Assets<TileSet>,
+    ) -> Option<&'a TileSet> {
+        tile_sets.get(self.0.get(&kind)?)
+    }
+
+    /// Return if every tile set is loaded.
+    #[must_use]
+    pub fn is_loaded(&self, tile_sets: &Assets<TileSet>) -> bool {
+        self.0.values().all(|handle| tile_sets.contains(handle))
+    }
+}
+
+/// A sprite piece of an auto tiled tile.
+#[derive(Bundle)]
+pub(crate) struct TilePiece {
+    sprite: AtlasSprite,
+    transform: Transform,
+    marker: SubTile,
+}
+
+impl TilePiece {
+    fn new(texture: Texture, offset: Vec2, flip: bool) -> TilePiece {
+        TilePiece {
+            sprite: AtlasSprite {
+                flip_y: flip,
+                ..AtlasSprite::new(texture)
+            },
+            transform: Transform::from_translation(offset.extend(-10.0)),
+            marker: SubTile,
+        }
+    }
+}
+
+/// Loader for [`TileSet`] asset
+#[derive(Default)]
+pub struct TileSetLoader;
+
+#[non_exhaustive]
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum TileSetError {
+    #[error("Could not load tile set asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid character in tile set asset: {0}")]
+    Utf8(#[from] std::string::FromUtf8Error),
+    #[error("Tile set asset is missing a rule")]
+    MissingRule,
+    #[error("Invalid rule in tile set asset: {0}")]
+    Rule(String),
+    #[error("Invalid variant in tile set asset: {0}")]
+    Variant(String),
+    #[error("Tile set have incorrect number of variants, expected {0}, but recieved {1}")]
+    VariantCount(usize, usize),
+    #[error("Invalid key in tile set asset: {0}")]
+    Key(String),
+}
+
+impl AssetLoader for TileSetLoader {
+    type Asset = TileSet;
+    type Settings = ();
+    type Error = TileSetError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).await?;
+
+        let string = String::from_utf8(bytes)?;
+
+        let mut rule = None;
+        let mut variants = None;
+
+        for line in string.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+
+            match key {
+                "rule" => {
+                    rule = Some(match value.trim() {
+                        "sub_tile" => AutoTileRule::SubTile,
+                        "cardinal" => AutoTileRule::Cardinal,
+                        "blob" => AutoTileRule::Blob,
+                        v => return Err(TileSetError::Rule(v.to_string())),
+                    });
+                }
+                "variants" => {
+                    variants = Some(
+                        value
+                            .split_whitespace()
+                            .map(|v| v.parse().map_err(|_| TileSetError::Variant(v.to_string())))
+                            .collect::<Result<Vec<usize>, _>>()?,
+                    );
+                }
+                k => return Err(TileSetError::Key(k.to_string())),
+            }
+        }
+
+        let rule = rule.ok_or(TileSetError::MissingRule)?;
+        let variants = variants.unwrap_or_else(|| (0..rule.variant_count()).collect());
+
+        if variants.len() != rule.variant_count() {
+            return Err(TileSetError::VariantCount(
+                rule.variant_count(),
+                variants.len(),
+            ));
+        }
+
+        Ok(TileSet { rule, variants })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tileset"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a neighbour with only the given side and corner connected, using the blob bit
+    /// order: north, north east, east, south east, south, south west, west, north west.
+    fn neighbour(connected: [bool; 8]) -> NeighbourTile {
+        let [north, north_east, east, south_east, south, south_west, west, north_west] = connected;
+        OctCompass {
+            north,
+            east,
+            south,
+            west,
+            north_east,
+            south_east,
+            south_west,
+            north_west,
+        }
+    }
+
+    #[test]
+    fn blob_masks_valid() {
+        assert_eq!(AutoTileRule::Blob.variant_count(), 47);
+        assert!(BLOB_MASKS.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(BLOB_MASKS[0], 0);
+        assert_eq!(BLOB_MASKS[46], u8::MAX);
+    }
+
+    #[test]
+    fn blob_isolated_and_surrounded() {
+        assert_eq!(AutoTileRule::blob_variant(neighbour([false; 8])), 0);
+        assert_eq!(AutoTileRule::blob_variant(neighbour([true; 8])), 46);
+    }
+
+    #[test]
+    fn blob_edge() {
+        // top edge of an area: every neighbour but the north row
+        let variant = AutoTileRule::blob_variant(neighbour([
+            false, false, true, true, true, true, true, false,
+        ]));
+        assert_eq!(BLOB_MASKS[variant], 0b0111_1100);
+    }
+
+    #[test]
+    fn blob_corner() {
+        // top left corner of an area: east, south east and south
+        let variant = AutoTileRule::blob_variant(neighbour([
+            false, false, true, true, true, false, false, false,
+        ]));
+        assert_eq!(BLOB_MASKS[variant], 0b0001_1100);
+    }
+
+    #[test]
+    fn blob_ignore_lone_corner() {
+        // a corner without both adjacent side is the same as no corner
+        let with_corner = AutoTileRule::blob_variant(neighbour([
+            true, true, false, false, false, false, false, true,
+        ]));
+        let without_corner = AutoTileRule::blob_variant(neighbour([
+            true, false, false, false, false, false, false, false,
+        ]));
+        assert_eq!(with_corner, without_corner);
+        assert_eq!(BLOB_MASKS[with_corner], 0b0000_0001);
+    }
+
+    #[test]
+    fn cardinal_isolated_and_surrounded() {
+        assert_eq!(AutoTileRule::cardinal_variant(neighbour([false; 8])), 0);
+        assert_eq!(AutoTileRule::cardinal_variant(neighbour([true; 8])), 15);
+    }
+
+    #[test]
+    fn cardinal_edge_and_corner() {
+        // top edge: east, south and west
+        assert_eq!(
+            AutoTileRule::cardinal_variant(neighbour([
+                false, false, true, true, true, true, true, false
+            ])),
+            2 | 4 | 8
+        );
+        // top left corner: east and south
+        assert_eq!(
+            AutoTileRule::cardinal_variant(neighbour([
+                false, false, true, true, true, false, false, false
+            ])),
+            2 | 4
+        );
+        // corner are ignored
+        assert_eq!(
+            AutoTileRule::cardinal_variant(neighbour([
+                false, true, false, true, false, true, false, true
+            ])),
+            0
+        );
+    }
+}
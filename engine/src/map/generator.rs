@@ -1,7 +1,6 @@
 use bevy::asset::LoadedFolder;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
-use rand::seq::SliceRandom;
 use rand::{thread_rng, Rng};
 
 use crate::prelude::*;
@@ -35,10 +34,7 @@ pub fn proc_generator(
     }
     // process every generator
     for (entity, gen, trans) in &generators {
-        let key = (
-            trans.translation.x / WIDTH as i32,
-            trans.translation.y / HEIGHT as i32,
-        );
+        let key = Map::room_pos(trans.translation);
 
         // despawn this generator
         commands.entity(entity).despawn();
@@ -101,7 +97,8 @@ pub fn proc_generator(
     }
 }
 
-/// Replace [`Room`] object with actual room
+/// Replace [`Room`] object with actual room layout in the [`Map`]. The tile entities are spawned by
+/// [`stream_rooms`]. A [`Room`] is only despawned once a layout was picked for it.
 pub fn fill_room(
     mut commands: Commands,
     mut map: ResMut<Map>,
@@ -115,14 +112,7 @@ pub fn fill_room(
         return;
     };
     for (entity, mut room, trans) in &mut rooms {
-        commands.entity(entity).despawn();
-        let mut ground_tile: Vec<(AtlasSprite, GridTransform, Transform)> =
-            Vec::with_capacity((WIDTH * HEIGHT) as usize);
-
-        let curr = (
-            trans.translation.x / WIDTH as i32,
-            trans.translation.y / HEIGHT as i32,
-        );
+        let curr = Map::room_pos(trans.translation);
 
         if !visited.0.is_empty() {
             room.0.north =
@@ -137,83 +127,12 @@ pub fn fill_room(
             visited.0.insert(curr, room.0);
         }
 
+        // keep the room to try again on the next run if no layout can be picked yet
         let Some(layout) = RoomList::pick_room(room.0, loaded_folder, &room_layouts) else {
-            return;
+            continue;
         };
 
-        map.rooms.insert(
-            (
-                trans.translation.x / WIDTH as i32,
-                trans.translation.y / HEIGHT as i32,
-            ),
-            layout,
-        );
-
-        for (row, y_og) in layout.layout.iter().zip(0i32..) {
-            for (tile, x_og) in row.iter().zip(0i32..) {
-                let x = x_og + trans.translation.x;
-                let y = y_og + trans.translation.y;
-
-                let position = GridTransform::from_xy(x, y);
-
-                match tile {
-                    TileType::Ground => {
-                        ground_tile.push((
-                            AtlasSprite::new(
-                                *[
-                                    Texture::Blank,
-                                    Texture::Blank,
-                                    Texture::Blank,
-                                    Texture::Blank,
-                                    Texture::Blank,
-                                    Texture::Blank,
-                                    Texture::Blank,
-                                    Texture::Soil,
-                                    Texture::Flower,
-                                    Texture::Grass,
-                                ]
-                                .choose(&mut thread_rng())
-                                .unwrap(),
-                            ),
-                            position,
-                            Transform::from_xyz(0.0, 0.0, -10.0),
-                        ));
-                    }
-
-                    TileType::Wall => {
-                        let neighbour = layout.get_neighbour_wall(UVec2::new(
-                            u32::try_from(x_og).unwrap(),
-                            u32::try_from(y_og).unwrap(),
-                        ));
-
-                        commands
-                            .spawn((position, Visibility::Inherited))
-                            .with_children(|t| {
-                                t.spawn(WallPiece::new(true, true, neighbour));
-                                t.spawn(WallPiece::new(true, false, neighbour));
-
-                                t.spawn(WallPiece::new(false, true, neighbour));
-                                t.spawn(WallPiece::new(false, false, neighbour));
-                            });
-                    }
-                    TileType::Door(dir) => {
-                        let texture = match dir {
-                            CompassDir::North => Texture::DoorN,
-                            CompassDir::East => Texture::DoorE,
-                            CompassDir::South => Texture::DoorS,
-                            CompassDir::West => Texture::DoorW,
-                            _ => unreachable!(),
-                        };
-                        commands.spawn((
-                            AtlasSprite::new(texture),
-                            position,
-                            Transform::from_xyz(0.0, 0.0, -10.0),
-                        ));
-                    }
-                }
-            }
-        }
-
-        commands.spawn_batch(ground_tile);
+        commands.entity(entity).despawn();
+        map.rooms.insert(curr, layout);
     }
 }
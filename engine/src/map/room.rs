@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+/// Rooms within this many room of the current room are spawned by [`stream_rooms`].
+pub static SPAWN_DISTANCE: i32 = 1;
+/// Rooms further than this many room from the current room are despawned by [`stream_rooms`].
+pub static DESPAWN_DISTANCE: i32 = 2;
+
+/// Component for the root entity of a spawned room. Every [`Tile`] of the room is a child of it.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(Transform, Visibility)]
+pub struct RoomRoot(pub (i32, i32));
+
+impl Map {
+    /// Get the root entity of a spawned room.
+    #[must_use]
+    pub fn room_entity(&self, room: (i32, i32)) -> Option<Entity> {
+        self.room_entities.get(&room).copied()
+    }
+
+    /// Iterate over every spawned tile entity of a room with their global grid position.
+    pub fn room_tiles(&self, room: (i32, i32)) -> impl Iterator<Item = (IVec2, Entity)> + '_ {
+        (0..u32::from(HEIGHT))
+            .flat_map(|y| (0..u32::from(WIDTH)).map(move |x| UVec2::new(x, y)))
+            .map(move |local| Map::room_to_world(room, local))
+            .filter_map(|position| Some((position, self.tile_entity(position)?)))
+    }
+
+    /// Spawn the root entity and every tile of a room. If the room is already spawned it is
+    /// despawned first, so this can also be used to rebuild a room. Return [`None`] if there is no
+    /// room there.
+    pub fn spawn_room(
+        &mut self,
+        commands: &mut Commands,
+        tile_sets: &TileSets,
+        tile_set_assets: &Assets<TileSet>,
+        room: (i32, i32),
+    ) -> Option<Entity> {
+        if !self.rooms.contains_key(&room) {
+            return None;
+        }
+
+        self.despawn_room(commands, room);
+
+        let entity = commands.spawn(RoomRoot(room)).id();
+        self.room_entities.insert(room, entity);
+
+        for y in 0..u32::from(HEIGHT) {
+            for x in 0..u32::from(WIDTH) {
+                self.spawn_tile(
+                    commands,
+                    tile_sets,
+                    tile_set_assets,
+                    Map::room_to_world(room, UVec2::new(x, y)),
+                );
+            }
+        }
+
+        Some(entity)
+    }
+
+    /// Despawn the root entity and every tile of a room. The room layout is kept in the map.
+    pub fn despawn_room(&mut self, commands: &mut Commands, room: (i32, i32)) {
+        let Some(entity) = self.room_entities.remove(&room) else {
+            return;
+        };
+
+        commands.entity(entity).despawn_recursive();
+        self.tiles
+            .retain(|position, _| Map::room_pos(*position) != room);
+    }
+}
+
+/// Spawn the rooms around [`Map::curr_room_pos`] and despawn the rooms far away from it, so only
+/// the rooms near the player keep their tile entities alive.
+///
+/// Rooms are spawned when within [`SPAWN_DISTANCE`] and despawned when further than
+/// [`DESPAWN_DISTANCE`], the gap stop a room from being rebuilt when walking back and forth.
+pub fn stream_rooms(
+    mut commands: Commands,
+    mut map: ResMut<Map>,
+    tile_sets: Res<TileSets>,
+    tile_set_assets: Res<Assets<TileSet>>,
+) {
+    if !tile_sets.is_loaded(&tile_set_assets) {
+        return;
+    }
+
+    let (x, y) = map.curr_room_pos;
+    let distance = |room: (i32, i32)| (room.0 - x).abs().max((room.1 - y).abs());
+
+    let to_spawn: Vec<_> = map
+        .rooms
+        .keys()
+        .copied()
+        .filter(|room| distance(*room) <= SPAWN_DISTANCE && map.room_entity(*room).is_none())
+        .collect();
+    let to_despawn: Vec<_> = map
+        .room_entities
+        .keys()
+        .copied()
+        .filter(|room| distance(*room) > DESPAWN_DISTANCE)
+        .collect();
+
+    // only touch the map when needed so it is not marked as changed every frame
+    if to_spawn.is_empty() && to_despawn.is_empty() {
+        return;
+    }
+
+    for room in to_despawn {
+        map.despawn_room(&mut commands, room);
+    }
+    for room in to_spawn {
+        map.spawn_room(&mut commands, &tile_sets, &tile_set_assets, room);
+    }
+}
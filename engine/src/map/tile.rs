@@ -0,0 +1,160 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+
+use crate::prelude::*;
+
+/// Marker component for a tile entity spawned from the [`Map`].
+#[derive(Component)]
+pub struct Tile;
+
+/// Event to change a tile of the [`Map`] at runtime, handled by the [`apply_set_tile`] system.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SetTile {
+    /// Global grid position of the tile.
+    pub position: IVec2,
+    /// New tile to place.
+    pub tile: TileType,
+}
+
+impl Map {
+    /// Get the entity of the tile at a global grid position.
+    #[must_use]
+    pub fn tile_entity(&self, position: IVec2) -> Option<Entity> {
+        self.tiles.get(&position).copied()
+    }
+
+    /// Change the tile at a global grid position in the stored layout. Return `false` if there is
+    /// no room there.
+    ///
+    /// This does not update the tile entities, send a [`SetTile`] event for that.
+    pub fn set_tile(&mut self, position: IVec2, tile: TileType) -> bool {
+        let Some(layout) = self.rooms.get_mut(&Map::room_pos(position)) else {
+            return false;
+        };
+
+        layout.set_tile(Map::local_pos(position), tile);
+        true
+    }
+
+    /// Spawn the entity for the tile at a global grid position, despawning the old one if there is
+    /// any. Return [`None`] and keep the old one if there is no room there, the room is not spawned
+    /// or the tile can not be spawned yet.
+    pub fn spawn_tile(
+        &mut self,
+        commands: &mut Commands,
+        tile_sets: &TileSets,
+        tile_set_assets: &Assets<TileSet>,
+        position: IVec2,
+    ) -> Option<Entity> {
+        let tile = self.get_tile(position)?;
+        let root = self.room_entity(Map::room_pos(position))?;
+        let transform = GridTransform::from_xy(position.x, position.y);
+
+        // look everything up first so the old tile is kept if the new one can not be spawned
+        let (pieces, texture) = if let Some(kind) = tile.tile_set() {
+            let tile_set = tile_sets.get(kind, tile_set_assets)?;
+            (
+                tile_set.pieces(kind, self.get_neighbour_tile(kind, position)),
+                None,
+            )
+        } else {
+            let texture = match tile {
+                TileType::Ground => *[
+                    Texture::Blank,
+                    Texture::Blank,
+                    Texture::Blank,
+                    Texture::Blank,
+                    Texture::Blank,
+                    Texture::Blank,
+                    Texture::Blank,
+                    Texture::Soil,
+                    Texture::Flower,
+                    Texture::Grass,
+                ]
+                .choose(&mut thread_rng())
+                .unwrap(),
+                TileType::Door(CompassDir::North) => Texture::DoorN,
+                TileType::Door(CompassDir::East) => Texture::DoorE,
+                TileType::Door(CompassDir::South) => Texture::DoorS,
+                TileType::Door(CompassDir::West) => Texture::DoorW,
+                tile => {
+                    warn!("Can not spawn {tile:?} at {position}");
+                    return None;
+                }
+            };
+            (Vec::new(), Some(texture))
+        };
+
+        if let Some(old) = self.tiles.remove(&position) {
+            commands.entity(old).despawn_recursive();
+        }
+
+        let entity = match texture {
+            Some(texture) => commands
+                .spawn((
+                    Tile,
+                    AtlasSprite::new(texture),
+                    transform,
+                    Transform::from_xyz(0.0, 0.0, -10.0),
+                ))
+                .id(),
+            None => commands
+                .spawn((Tile, transform, Visibility::Inherited))
+                .with_children(|t| {
+                    for piece in pieces {
+                        t.spawn(piece);
+                    }
+                })
+                .id(),
+        };
+
+        commands.entity(entity).set_parent(root);
+
+        self.tiles.insert(position, entity);
+
+        Some(entity)
+    }
+}
+
+/// Apply every [`SetTile`] event to the [`Map`], respawning the changed tile and re-tiling any auto
+/// tiled tile around it.
+pub fn apply_set_tile(
+    mut commands: Commands,
+    mut map: ResMut<Map>,
+    tile_sets: Res<TileSets>,
+    tile_set_assets: Res<Assets<TileSet>>,
+    mut events: EventReader<SetTile>,
+) {
+    for SetTile { position, tile } in events.read().copied() {
+        if !tile.is_valid() {
+            warn!("Ignoring invalid tile {tile:?} at {position}");
+            continue;
+        }
+        if !map.set_tile(position, tile) {
+            continue;
+        }
+
+        map.spawn_tile(&mut commands, &tile_sets, &tile_set_assets, position);
+
+        // the neighbour only need to be respawned if their visual depend on this tile
+        for offset in [
+            IVec2::NEG_Y,
+            IVec2::X,
+            IVec2::Y,
+            IVec2::NEG_X,
+            IVec2::NEG_Y + IVec2::X,
+            IVec2::ONE,
+            IVec2::Y + IVec2::NEG_X,
+            IVec2::NEG_ONE,
+        ] {
+            let neighbour = position + offset;
+            if map
+                .get_tile(neighbour)
+                .is_some_and(|t| t.tile_set().is_some())
+            {
+                map.spawn_tile(&mut commands, &tile_sets, &tile_set_assets, neighbour);
+            }
+        }
+    }
+}
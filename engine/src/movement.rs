@@ -0,0 +1,196 @@
+//! Grid movement shared by the player and the AI.
+//!
+//! Movement is requested by sending a [`MoveIntent`] event. The [`resolve_movement`] system check
+//! the move against the [`Map`] tile and any [`Blocking`] entity in the [`GridIndex`], then either
+//! move the entity and start its [`TransformAnimation`], or send a [`Bump`] event. Either way the
+//! entity [`Facing`] is turned toward the direction. A successful move by an [`Actor`] also send
+//! an [`ActionTaken`] event to end its turn.
+//!
+//! Diagonal movement is only allowed when [`MovementOptions::diagonal`] is set. A diagonal step can
+//! never cut the corner of a tile that can not be walked on, and can not enter a door.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+use crate::prelude::*;
+
+/// Duration of the animation when moving a single tile.
+pub static MOVE_DURATION: Duration = Duration::from_millis(100);
+/// Duration of the animation when moving through a door.
+pub static DOOR_DURATION: Duration = Duration::from_millis(200);
+
+/// Event requesting an entity to move one step in a direction.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct MoveIntent {
+    /// Entity to move.
+    pub entity: Entity,
+    /// Direction to move in.
+    pub direction: Direction,
+}
+
+/// Event sent when a [`MoveIntent`] was blocked.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct Bump {
+    /// Entity that tried to move.
+    pub entity: Entity,
+    /// Direction it tried to move in.
+    pub direction: Direction,
+    /// Grid position it bumped into.
+    pub position: IVec2,
+    /// The [`Blocking`] entity it bumped into, [`None`] if it bumped into a tile.
+    pub blocker: Option<Entity>,
+}
+
+/// Resource holding the options for [`resolve_movement`].
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct MovementOptions {
+    /// Allow diagonal step.
+    pub diagonal: bool,
+}
+
+impl Map {
+    /// Get the position an entity end up at when moving a step from a position. Moving into a
+    /// door in the direction it face move through it and the door of the next room. Return
+    /// [`None`] if the tile can not be walked on, the door face another direction or any tile past
+    /// it can not be walked on.
+    ///
+    /// A diagonal step can only end on ground and both tile it pass beside must be walkable.
+    #[must_use]
+    pub fn move_target(&self, from: IVec2, direction: Direction) -> Option<IVec2> {
+        if let Some((horz, vert)) = direction.split() {
+            let walkable = |dir: Direction| {
+                self.get_tile(from + dir.offset())
+                    .is_some_and(TileType::is_walkable)
+            };
+            let target = from + direction.offset();
+
+            return (matches!(self.get_tile(target), Some(TileType::Ground))
+                && walkable(horz)
+                && walkable(vert))
+            .then_some(target);
+        }
+
+        let step = GridTransform::from_xy(from.x, from.y).translate(direction, 1);
+
+        match self.get_tile(step.translation)? {
+            TileType::Door(dir) => {
+                let walkable = |amount| {
+                    let position = step.translate(direction, amount).translation;
+                    self.get_tile(position)
+                        .is_some_and(TileType::is_walkable)
+                        .then_some(position)
+                };
+
+                if Direction::from(dir) != direction {
+                    return None;
+                }
+                walkable(1).and_then(|_| walkable(2))
+            }
+            tile if tile.is_walkable() => Some(step.translation),
+            _ => None,
+        }
+    }
+}
+
+/// System to resolve every [`MoveIntent`]. Diagonal intent are ignored unless allowed by
+/// [`MovementOptions`].
+///
+/// Entity that are still animating ignore the intent, unless they are an [`Actor`] which are only
+/// limited by the turn order. Their animation restart from the current position instead.
+///
+/// Intent from an [`Actor`] that is not [`TakingTurn`] are ignored. A move always end the actor
+/// turn, while a bump only end the turn of non player actor, so the AI can not get stuck.
+#[allow(clippy::type_complexity)]
+pub fn resolve_movement(
+    mut intents: EventReader<MoveIntent>,
+    options: Res<MovementOptions>,
+    mut bumps: EventWriter<Bump>,
+    mut actions: EventWriter<ActionTaken>,
+    mut map: ResMut<Map>,
+    mut index: ResMut<GridIndex>,
+    mut movers: Query<(
+        &mut GridTransform,
+        &mut TransformAnimation,
+        Option<&mut Facing>,
+        Has<Actor>,
+        Has<TakingTurn>,
+        Has<Player>,
+    )>,
+) {
+    // the turn is only ended once the commands are applied, so only the first action count
+    let mut acted = HashSet::new();
+
+    for MoveIntent { entity, direction } in intents.read().copied() {
+        let Ok((mut transform, mut animation, facing, is_actor, taking_turn, is_player)) =
+            movers.get_mut(entity)
+        else {
+            continue;
+        };
+
+        if direction.is_zero()
+            || (direction.is_diagonal() && !options.diagonal)
+            || (is_actor && (!taking_turn || acted.contains(&entity)))
+            || (!is_actor && !animation.duration.is_zero())
+        {
+            continue;
+        }
+
+        // face the direction even if the move end up blocked
+        if let Some(mut facing) = facing {
+            if facing.direction() != direction {
+                facing.set(direction);
+            }
+        }
+
+        let from = transform.translation;
+        let headed = transform.translate(direction, 1).translation;
+
+        let target = map.move_target(from, direction);
+        let blocker = target
+            .and_then(|t| index.blocker_at(t))
+            .filter(|b| *b != entity);
+
+        let (Some(target), None) = (target, blocker) else {
+            bumps.send(Bump {
+                entity,
+                direction,
+                position: target.unwrap_or(headed),
+                blocker,
+            });
+
+            if is_actor && !is_player {
+                acted.insert(entity);
+                actions.send(ActionTaken {
+                    entity,
+                    cost: ACTION_COST,
+                });
+            }
+            continue;
+        };
+
+        let duration = if target == headed {
+            MOVE_DURATION
+        } else {
+            DOOR_DURATION
+        };
+        animation.start(*transform, duration);
+        transform.translation = target;
+
+        if is_actor {
+            acted.insert(entity);
+            actions.send(ActionTaken {
+                entity,
+                cost: ACTION_COST,
+            });
+        }
+
+        // keep the index up to date so later intent this frame see the move
+        index.insert(entity, target);
+
+        if is_player && Map::room_pos(target) != map.curr_room_pos {
+            map.curr_room_pos = Map::room_pos(target);
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! A* pathfinding over the [`Map`] grid.
+//!
+//! Path are found over the global grid, so they cross room border through door the same way
+//! [`Map::move_target`] does. Diagonal step can be allowed with [`PathOptions::diagonal`], and
+//! follow the same corner cutting rule as diagonal movement.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::prelude::*;
+
+/// Options for [`find_path`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PathOptions {
+    /// Allow diagonal step.
+    pub diagonal: bool,
+    /// Avoid position with a [`Blocking`] entity, except for the goal.
+    pub avoid_blocking: bool,
+}
+
+/// Get every position reachable in a single step from a position, ignoring any entity.
+pub(crate) fn step_targets(map: &Map, position: IVec2, diagonal: bool) -> Vec<IVec2> {
+    let directions: &[Direction] = if diagonal {
+        &Direction::ALL
+    } else {
+        &Direction::CARDINAL
+    };
+
+    directions
+        .iter()
+        .filter_map(|dir| map.move_target(position, *dir))
+        .collect()
+}
+
+/// Get every position reachable in a single step from a position, with the cost of the step.
+fn neighbours(
+    map: &Map,
+    index: Option<&GridIndex>,
+    position: IVec2,
+    goal: IVec2,
+    options: PathOptions,
+) -> Vec<(IVec2, u32)> {
+    step_targets(map, position, options.diagonal)
+        .into_iter()
+        .filter(|p| {
+            *p == goal || !options.avoid_blocking || index.is_none_or(|index| !index.is_blocked(*p))
+        })
+        .map(|p| (p, step_distance(position, p, options.diagonal)))
+        .collect()
+}
+
+/// Distance between 2 position, used both as the step cost and the heuristic.
+pub(crate) fn step_distance(a: IVec2, b: IVec2, diagonal: bool) -> u32 {
+    let diff = (a - b).abs().as_uvec2();
+    if diagonal {
+        diff.max_element()
+    } else {
+        diff.element_sum()
+    }
+}
+
+/// Find the shortest path between 2 global grid position.
+///
+/// Return the position after each step, ending with the goal and not including the start, or
+/// [`None`] if the goal can not be reached. The [`GridIndex`] is only needed when
+/// [`PathOptions::avoid_blocking`] is set.
+#[must_use]
+pub fn find_path(
+    map: &Map,
+    index: Option<&GridIndex>,
+    start: IVec2,
+    goal: IVec2,
+    options: PathOptions,
+) -> Option<Vec<IVec2>> {
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<IVec2, IVec2> = HashMap::new();
+    let mut cost: HashMap<IVec2, u32> = HashMap::new();
+
+    open.push(Reverse((
+        step_distance(start, goal, options.diagonal),
+        start.x,
+        start.y,
+    )));
+    cost.insert(start, 0);
+
+    while let Some(Reverse((_, x, y))) = open.pop() {
+        let position = IVec2::new(x, y);
+
+        if position == goal {
+            let mut path = vec![goal];
+            while let Some(prev) = came_from.get(path.last().unwrap()) {
+                path.push(*prev);
+            }
+            path.pop(); // remove the start
+            path.reverse();
+            return Some(path);
+        }
+
+        let curr_cost = cost[&position];
+
+        for (next, step_cost) in neighbours(map, index, position, goal, options) {
+            let next_cost = curr_cost + step_cost;
+            if cost.get(&next).is_some_and(|c| *c <= next_cost) {
+                continue;
+            }
+
+            cost.insert(next, next_cost);
+            came_from.insert(next, position);
+            open.push(Reverse((
+                next_cost + step_distance(next, goal, options.diagonal),
+                next.x,
+                next.y,
+            )));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::test_rooms::{empty_room, single_room};
+
+    #[test]
+    fn straight_path() {
+        let map = single_room(&empty_room());
+        let path = find_path(
+            &map,
+            None,
+            IVec2::new(1, 1),
+            IVec2::new(5, 1),
+            PathOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            path,
+            vec![
+                IVec2::new(2, 1),
+                IVec2::new(3, 1),
+                IVec2::new(4, 1),
+                IVec2::new(5, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn path_around_wall() {
+        let mut room = empty_room();
+        // vertical wall with a gap at the bottom
+        for y in 1..(HEIGHT - 2) {
+            room.set_tile(UVec2::new(5, y.into()), TileType::Wall);
+        }
+        let map = single_room(&room);
+
+        let path = find_path(
+            &map,
+            None,
+            IVec2::new(1, 1),
+            IVec2::new(9, 1),
+            PathOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(path.last(), Some(&IVec2::new(9, 1)));
+        assert!(path.contains(&IVec2::new(5, i32::from(HEIGHT) - 2)));
+        assert!(path.iter().all(|p| map.get_tile(*p).unwrap().is_walkable()));
+    }
+
+    #[test]
+    fn no_path_when_enclosed() {
+        let mut room = empty_room();
+        for x in 0..3 {
+            room.set_tile(UVec2::new(x, 3), TileType::Wall);
+        }
+        for y in 0..3 {
+            room.set_tile(UVec2::new(3, y), TileType::Wall);
+        }
+        let map = single_room(&room);
+
+        assert!(find_path(
+            &map,
+            None,
+            IVec2::new(1, 1),
+            IVec2::new(9, 9),
+            PathOptions::default(),
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn diagonal_path() {
+        let map = single_room(&empty_room());
+        let options = PathOptions {
+            diagonal: true,
+            ..default()
+        };
+
+        let path = find_path(&map, None, IVec2::new(1, 1), IVec2::new(4, 4), options).unwrap();
+
+        assert_eq!(
+            path,
+            vec![IVec2::new(2, 2), IVec2::new(3, 3), IVec2::new(4, 4)]
+        );
+    }
+
+    #[test]
+    fn diagonal_does_not_cut_corner() {
+        let mut room = empty_room();
+        room.set_tile(UVec2::new(2, 1), TileType::Wall);
+        let map = single_room(&room);
+        let options = PathOptions {
+            diagonal: true,
+            ..default()
+        };
+
+        let path = find_path(&map, None, IVec2::new(1, 1), IVec2::new(2, 2), options).unwrap();
+
+        assert_eq!(path, vec![IVec2::new(1, 2), IVec2::new(2, 2)]);
+    }
+
+    #[test]
+    fn path_through_door() {
+        let vert_mid = u32::from(HEIGHT - 1) / 2;
+
+        let mut west = empty_room();
+        west.set_tile(
+            UVec2::new(u32::from(WIDTH) - 1, vert_mid),
+            TileType::Door(CompassDir::East),
+        );
+        let mut east = empty_room();
+        east.set_tile(UVec2::new(0, vert_mid), TileType::Door(CompassDir::West));
+
+        let map = Map::new([((0, 0), west), ((1, 0), east)].into_iter().collect());
+
+        let start = Map::room_to_world((0, 0), UVec2::new(u32::from(WIDTH) - 2, vert_mid));
+        let goal = Map::room_to_world((1, 0), UVec2::new(1, vert_mid));
+
+        let path = find_path(&map, None, start, goal, PathOptions::default()).unwrap();
+
+        // moving into the door move through both door in a single step
+        assert_eq!(path, vec![goal]);
+    }
+
+    #[test]
+    fn no_path_through_blocked_door() {
+        let vert_mid = u32::from(HEIGHT - 1) / 2;
+
+        let mut west = empty_room();
+        west.set_tile(
+            UVec2::new(u32::from(WIDTH) - 1, vert_mid),
+            TileType::Door(CompassDir::East),
+        );
+        // the east room has no door, so the tile past the door is a wall
+        let map = Map::new(
+            [((0, 0), west), ((1, 0), empty_room())]
+                .into_iter()
+                .collect(),
+        );
+
+        let start = Map::room_to_world((0, 0), UVec2::new(u32::from(WIDTH) - 2, vert_mid));
+        let goal = Map::room_to_world((1, 0), UVec2::new(1, vert_mid));
+
+        assert_eq!(map.move_target(start, Direction::Right), None);
+        assert!(find_path(&map, None, start, goal, PathOptions::default()).is_none());
+    }
+
+    #[test]
+    fn no_step_onto_wall_past_door() {
+        let vert_mid = u32::from(HEIGHT - 1) / 2;
+
+        let mut west = empty_room();
+        west.set_tile(
+            UVec2::new(u32::from(WIDTH) - 1, vert_mid),
+            TileType::Door(CompassDir::East),
+        );
+        let mut east = empty_room();
+        east.set_tile(UVec2::new(0, vert_mid), TileType::Door(CompassDir::West));
+        east.set_tile(UVec2::new(1, vert_mid), TileType::Wall);
+        let map = Map::new([((0, 0), west), ((1, 0), east)].into_iter().collect());
+
+        let start = Map::room_to_world((0, 0), UVec2::new(u32::from(WIDTH) - 2, vert_mid));
+
+        assert_eq!(map.move_target(start, Direction::Right), None);
+    }
+
+    #[test]
+    fn door_only_enter_in_its_direction() {
+        let mut room = empty_room();
+        room.set_tile(UVec2::new(5, 5), TileType::Door(CompassDir::North));
+        let map = single_room(&room);
+
+        // moving sideway into the door does nothing
+        assert_eq!(map.move_target(IVec2::new(4, 5), Direction::Right), None);
+        // moving north through it land 2 tile past it
+        assert_eq!(
+            map.move_target(IVec2::new(5, 6), Direction::Up),
+            Some(IVec2::new(5, 3))
+        );
+        // a door leading out of the map can not be walked through
+        room.set_tile(UVec2::new(5, 0), TileType::Ground);
+        room.set_tile(UVec2::new(5, 1), TileType::Door(CompassDir::North));
+        let map = single_room(&room);
+        assert_eq!(map.move_target(IVec2::new(5, 2), Direction::Up), None);
+    }
+
+    #[test]
+    fn avoid_blocking_entity() {
+        let map = single_room(&empty_room());
+
+        let blocker = Entity::from_raw(0);
+        let mut index = GridIndex::default();
+        index.insert(blocker, IVec2::new(3, 1));
+        index.set_blocking(blocker, true);
+
+        let options = PathOptions {
+            avoid_blocking: true,
+            ..default()
+        };
+        let path = find_path(
+            &map,
+            Some(&index),
+            IVec2::new(1, 1),
+            IVec2::new(5, 1),
+            options,
+        )
+        .unwrap();
+
+        assert!(!path.contains(&IVec2::new(3, 1)));
+        assert_eq!(path.last(), Some(&IVec2::new(5, 1)));
+
+        // the goal itself can be blocked
+        let path = find_path(
+            &map,
+            Some(&index),
+            IVec2::new(1, 1),
+            IVec2::new(3, 1),
+            options,
+        )
+        .unwrap();
+        assert_eq!(path.last(), Some(&IVec2::new(3, 1)));
+    }
+}
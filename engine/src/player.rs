@@ -9,10 +9,19 @@ use crate::prelude::*;
 #[require(
     AtlasSprite(player_sprite),
     GridTransform(player_transform),
-    TransformAnimation
+    TransformAnimation,
+    Blocking,
+    Facing,
+    Actor,
+    Viewshed(player_viewshed),
+    Name(player_name)
 )]
 pub struct Player;
 
+fn player_name() -> Name {
+    Name::new("Player")
+}
+
 fn player_sprite() -> AtlasSprite {
     AtlasSprite::new(Texture::Player)
 }
@@ -20,3 +29,7 @@ fn player_sprite() -> AtlasSprite {
 fn player_transform() -> GridTransform {
     GridTransform::from_xy(1, 1)
 }
+
+fn player_viewshed() -> Viewshed {
+    Viewshed::new(8)
+}
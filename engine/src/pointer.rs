@@ -0,0 +1,206 @@
+//! Mouse interaction with the grid using `bevy_picking`.
+//!
+//! Every visible sprite can be picked, so the grid position under the pointer is found from the
+//! picking hit with [`GridTransform::from_world`]:
+//! - [`hover_tile`] keep the [`HoveredTile`] resource up to date, which is outlined by
+//!   [`highlight_hovered`] and described by the [`Tooltip`] in [`update_tooltip`].
+//! - [`click_to_move`] give the player a [`MovePath`] to the clicked tile, that the game follow one
+//!   turn at a time.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+/// Colour of the outline around the [`HoveredTile`].
+pub static HIGHLIGHT_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.6);
+
+/// Resource holding the grid position under the pointer, [`None`] if the pointer is not over any
+/// sprite.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HoveredTile(pub Option<IVec2>);
+
+/// Path an entity is following, one step per turn.
+#[derive(Component, Clone, Debug, Default)]
+pub struct MovePath(VecDeque<IVec2>);
+
+impl MovePath {
+    /// Create a new [`MovePath`] from the position after each step, as returned by [`find_path`].
+    #[must_use]
+    pub fn new(steps: impl IntoIterator<Item = IVec2>) -> Self {
+        MovePath(steps.into_iter().collect())
+    }
+
+    /// Get the direction of the next step from a position, skipping any step already reached.
+    /// Return [`None`] once the path is done.
+    pub fn next_direction(&mut self, from: IVec2) -> Option<Direction> {
+        while self.0.front() == Some(&from) {
+            self.0.pop_front();
+        }
+
+        self.0
+            .front()
+            .map(|next| Direction::from_offset(*next - from))
+    }
+
+    /// Return if every step of the path is done.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Marker component for the tooltip text describing the [`HoveredTile`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+#[require(Text, Node, Visibility(tooltip_visibility))]
+pub struct Tooltip;
+
+fn tooltip_visibility() -> Visibility {
+    Visibility::Hidden
+}
+
+/// System to update the [`HoveredTile`] from the picking events.
+pub fn hover_tile(
+    mut moves: EventReader<Pointer<Move>>,
+    mut overs: EventReader<Pointer<Over>>,
+    mut outs: EventReader<Pointer<Out>>,
+    mut hovered: ResMut<HoveredTile>,
+) {
+    let mut position = hovered.0;
+
+    // the pointer leave the old sprite before entering the new one
+    if outs.read().count() > 0 {
+        position = None;
+    }
+
+    let hits = overs
+        .read()
+        .filter_map(|e| e.event.hit.position)
+        .chain(moves.read().filter_map(|e| e.event.hit.position));
+    if let Some(hit) = hits.last() {
+        position = Some(GridTransform::from_world(hit.xy()).translation);
+    }
+
+    hovered.set_if_neq(HoveredTile(position));
+}
+
+/// System to outline the [`HoveredTile`].
+pub fn highlight_hovered(hovered: Res<HoveredTile>, mut gizmos: Gizmos) {
+    let Some(position) = hovered.0 else {
+        return;
+    };
+
+    gizmos.rect_2d(
+        Isometry2d::from_translation(
+            GridTransform::from_xy(position.x, position.y)
+                .as_vec3()
+                .xy(),
+        ),
+        Vec2::splat(f32::from(TILE_SIZE)),
+        HIGHLIGHT_COLOR,
+    );
+}
+
+/// System to spawn the [`Tooltip`].
+pub fn setup_tooltip(mut commands: Commands) {
+    commands.spawn((
+        Tooltip,
+        Node {
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        TextFont::from_font_size(14.0),
+    ));
+}
+
+/// System to show the tile type and the name of every visible entity on the [`HoveredTile`] next
+/// to the cursor. The tooltip is only touched when its content or position change, so the text is
+/// not laid out again every frame.
+#[allow(clippy::type_complexity)]
+pub fn update_tooltip(
+    hovered: Res<HoveredTile>,
+    map: Res<Map>,
+    index: Res<GridIndex>,
+    names: Query<(&Name, &Visibility)>,
+    window: Single<&Window>,
+    tooltip: Single<(&mut Text, &mut Node, &mut Visibility), (With<Tooltip>, Without<Name>)>,
+) {
+    let (mut text, mut node, mut visibility) = tooltip.into_inner();
+
+    let (Some(position), Some(cursor)) = (hovered.0, window.cursor_position()) else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+    let Some(tile) = map.get_tile(position) else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+
+    let mut lines = vec![tile.name().to_string()];
+    lines.extend(
+        index
+            .entities_at(position)
+            .iter()
+            .filter_map(|e| names.get(*e).ok())
+            .filter(|(_, v)| **v != Visibility::Hidden)
+            .map(|(name, _)| name.to_string()),
+    );
+
+    let content = lines.join("\n");
+    if text.0 != content {
+        text.0 = content;
+    }
+
+    let (left, top) = (Val::Px(cursor.x + 12.0), Val::Px(cursor.y + 12.0));
+    if node.left != left || node.top != top {
+        node.left = left;
+        node.top = top;
+    }
+
+    visibility.set_if_neq(Visibility::Inherited);
+}
+
+/// System to give the player a [`MovePath`] to a clicked tile. Clicking a tile that can not be
+/// reached stop the current path.
+pub fn click_to_move(
+    mut commands: Commands,
+    mut clicks: EventReader<Pointer<Click>>,
+    map: Res<Map>,
+    index: Res<GridIndex>,
+    options: Res<MovementOptions>,
+    player: Single<(Entity, &GridTransform), With<Player>>,
+) {
+    let (entity, transform) = *player;
+
+    let Some(goal) = clicks
+        .read()
+        .filter(|e| e.event.button == PointerButton::Primary)
+        .filter_map(|e| e.event.hit.position)
+        .last()
+        .map(|hit| GridTransform::from_world(hit.xy()).translation)
+    else {
+        return;
+    };
+
+    let path = map
+        .get_tile(goal)
+        .filter(|t| t.is_walkable() && map.is_explored(goal))
+        .and_then(|_| {
+            find_path(
+                &map,
+                Some(&index),
+                transform.translation,
+                goal,
+                PathOptions {
+                    diagonal: options.diagonal,
+                    avoid_blocking: true,
+                },
+            )
+        });
+
+    match path {
+        Some(path) => commands.entity(entity).insert(MovePath::new(path)),
+        None => commands.entity(entity).remove::<MovePath>(),
+    };
+}
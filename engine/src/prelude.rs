@@ -1,3 +1,6 @@
 //! Import all commonly used engine features and components.
 
-pub use crate::{animation::*, atlas::*, grid::*, map::*, player::*, *};
+pub use crate::{
+    animation::*, atlas::*, distance_map::*, facing::*, fov::*, grid::*, input::*, map::*,
+    movement::*, pathfinding::*, player::*, pointer::*, render::*, spatial::*, turn::*, *,
+};
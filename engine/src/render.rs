@@ -4,20 +4,55 @@ use bevy::prelude::*;
 
 use crate::prelude::*;
 
-/// Unload any atlast sprite that is outside of the camera "viewport"
-pub fn unload_outside(
-    camera_trans: Single<&GridTransform, With<Camera>>,
-    mut sprite: Query<(&mut Visibility, &GridTransform)>,
+/// Tint for tile that have been explored but are not currently visible.
+pub static EXPLORED_TINT: Color = Color::srgb(0.35, 0.35, 0.35);
+
+/// Render the fog of war using the player [`Viewshed`].
+///
+/// Tile that have never been seen are hidden, tile that have been explored but are not visible
+/// are dimmed with [`EXPLORED_TINT`] and visible tile are rendered normally. Any other grid entity
+/// is hidden when outside of the player view.
+#[allow(clippy::type_complexity)]
+pub fn fog_of_war(
+    map: Res<Map>,
+    viewshed: Single<&Viewshed, With<Player>>,
+    mut tiles: Query<(Entity, &GridTransform, &mut Visibility, Option<&Children>), With<Tile>>,
+    mut tile_sprites: Query<&mut AtlasSprite, Or<(With<Tile>, With<SubTile>)>>,
+    mut entities: Query<
+        (&GridTransform, &mut Visibility),
+        (With<AtlasSprite>, Without<Tile>, Without<Player>),
+    >,
 ) {
-    for (mut vis, trans) in &mut sprite {
-        let diff = (trans.translation - camera_trans.translation)
-            .abs()
-            .as_vec2();
+    for (entity, transform, mut vis, children) in &mut tiles {
+        let position = transform.translation;
 
-        *vis = if diff.x > (WIDTH / 2).into() || diff.y > (HEIGHT / 2).into() {
-            Visibility::Hidden
+        if !map.is_explored(position) {
+            vis.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+        vis.set_if_neq(Visibility::Inherited);
+
+        let tint = if viewshed.visible.contains(&position) {
+            Color::WHITE
         } else {
-            Visibility::Visible
+            EXPLORED_TINT
         };
+
+        // the sprite is either on the tile itself or on its sub tile
+        let mut sprites =
+            tile_sprites.iter_many_mut(children.into_iter().flatten().chain([&entity]));
+        while let Some(mut sprite) = sprites.fetch_next() {
+            if sprite.tint != tint {
+                sprite.tint = tint;
+            }
+        }
+    }
+
+    for (transform, mut vis) in &mut entities {
+        vis.set_if_neq(if viewshed.visible.contains(&transform.translation) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
     }
 }
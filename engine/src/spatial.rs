@@ -0,0 +1,157 @@
+//! Spatial index for grid entities.
+//!
+//! The engine keep a [`GridIndex`] resource mapping each grid position to the entities on it, so
+//! asking what is at a position does not need to scan every [`GridTransform`]. Only entities with
+//! the [`Indexed`] marker are indexed, which is required by [`Blocking`] and [`Actor`]. They are
+//! added and removed by the [`Indexed`] component hooks, and moved by the [`update_grid_index`]
+//! system when their [`GridTransform`] change.
+//!
+//! Map [`Tile`], rooms and the camera are not indexed, the [`Map`] already track the tiles.
+
+use bevy::ecs::component::ComponentId;
+use bevy::ecs::world::DeferredWorld;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+
+use crate::prelude::*;
+
+/// Marker component for entity that is tracked by the [`GridIndex`].
+#[derive(Component, Default)]
+#[require(GridTransform)]
+#[component(on_add = index_grid_transform, on_remove = unindex_grid_transform)]
+pub struct Indexed;
+
+/// Marker component for entity that block other entity from moving onto its position.
+#[derive(Component, Default)]
+#[require(Indexed)]
+#[component(on_add = add_blocking, on_remove = remove_blocking)]
+pub struct Blocking;
+
+/// Resource holding the index from grid position to entities.
+#[derive(Resource, Default, Debug)]
+pub struct GridIndex {
+    cells: HashMap<IVec2, Vec<Entity>>,
+    positions: HashMap<Entity, IVec2>,
+    blocking: HashSet<Entity>,
+}
+
+impl GridIndex {
+    /// Insert an entity at a position, moving it if it is already indexed.
+    pub fn insert(&mut self, entity: Entity, position: IVec2) {
+        if self.positions.get(&entity) == Some(&position) {
+            return;
+        }
+
+        self.remove(entity);
+        self.cells.entry(position).or_default().push(entity);
+        self.positions.insert(entity, position);
+    }
+
+    /// Remove an entity from the index.
+    pub fn remove(&mut self, entity: Entity) {
+        let Some(position) = self.positions.remove(&entity) else {
+            return;
+        };
+
+        if let Some(cell) = self.cells.get_mut(&position) {
+            cell.retain(|e| *e != entity);
+            if cell.is_empty() {
+                self.cells.remove(&position);
+            }
+        }
+    }
+
+    /// Mark an entity as [`Blocking`] or not. This is done automatically by the [`Blocking`]
+    /// component hooks.
+    pub fn set_blocking(&mut self, entity: Entity, blocking: bool) {
+        if blocking {
+            self.blocking.insert(entity);
+        } else {
+            self.blocking.remove(&entity);
+        }
+    }
+
+    /// Get the indexed position of an entity.
+    #[must_use]
+    pub fn position_of(&self, entity: Entity) -> Option<IVec2> {
+        self.positions.get(&entity).copied()
+    }
+
+    /// Get every entity at a position.
+    #[must_use]
+    pub fn entities_at(&self, position: IVec2) -> &[Entity] {
+        self.cells.get(&position).map_or(&[], Vec::as_slice)
+    }
+
+    /// Get the [`Blocking`] entity at a position if there is any.
+    #[must_use]
+    pub fn blocker_at(&self, position: IVec2) -> Option<Entity> {
+        self.entities_at(position)
+            .iter()
+            .copied()
+            .find(|e| self.blocking.contains(e))
+    }
+
+    /// Return if there is a [`Blocking`] entity at a position.
+    #[must_use]
+    pub fn is_blocked(&self, position: IVec2) -> bool {
+        self.blocker_at(position).is_some()
+    }
+
+    /// Iterate over every entity within a radius of a position, using the chessboard distance so
+    /// the radius cover a square.
+    pub fn entities_in_radius(
+        &self,
+        center: IVec2,
+        radius: i32,
+    ) -> impl Iterator<Item = (IVec2, Entity)> + '_ {
+        (-radius..=radius)
+            .flat_map(move |y| (-radius..=radius).map(move |x| center + IVec2::new(x, y)))
+            .flat_map(|position| {
+                self.entities_at(position)
+                    .iter()
+                    .map(move |e| (position, *e))
+            })
+    }
+}
+
+/// Hook to index an entity when [`Indexed`] is added.
+fn index_grid_transform(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+    let Some(position) = world.get::<GridTransform>(entity).map(|t| t.translation) else {
+        return;
+    };
+
+    if let Some(mut index) = world.get_resource_mut::<GridIndex>() {
+        index.insert(entity, position);
+    }
+}
+
+/// Hook to remove an entity from the index when [`Indexed`] is removed.
+fn unindex_grid_transform(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+    if let Some(mut index) = world.get_resource_mut::<GridIndex>() {
+        index.remove(entity);
+    }
+}
+
+fn add_blocking(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+    if let Some(mut index) = world.get_resource_mut::<GridIndex>() {
+        index.set_blocking(entity, true);
+    }
+}
+
+fn remove_blocking(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+    if let Some(mut index) = world.get_resource_mut::<GridIndex>() {
+        index.set_blocking(entity, false);
+    }
+}
+
+/// System to move [`Indexed`] entities in the [`GridIndex`] when their [`GridTransform`] change.
+#[allow(clippy::type_complexity)]
+pub fn update_grid_index(
+    mut index: ResMut<GridIndex>,
+    query: Query<(Entity, &GridTransform), (Changed<GridTransform>, With<Indexed>)>,
+) {
+    for (entity, grid) in &query {
+        index.insert(entity, grid.translation);
+    }
+}
@@ -0,0 +1,119 @@
+//! Turn scheduler using an energy system.
+//!
+//! Every [`Actor`] accumulate energy at the rate of its speed, and can act once it have at least
+//! [`ACTION_COST`] energy. The [`schedule_turns`] system give the [`TakingTurn`] marker to the
+//! actors whose turn it is, every non player actor first, and the player only once they are all
+//! done. An actor end its turn by sending an [`ActionTaken`] event, handled by [`end_turn`].
+//!
+//! Turns are not blocked by [`TransformAnimation`], so animations keep playing while the other
+//! actors take their turn.
+
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+/// Energy needed for an [`Actor`] to take a turn, and the cost of a standard action.
+pub static ACTION_COST: u32 = 100;
+
+/// Entity taking part in the turn order.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(Indexed)]
+pub struct Actor {
+    /// Energy gained every tick. An actor with a speed of [`ACTION_COST`] act once per tick.
+    pub speed: u32,
+    /// Energy accumulated so far.
+    pub energy: u32,
+}
+
+impl Default for Actor {
+    fn default() -> Self {
+        Actor::new(ACTION_COST)
+    }
+}
+
+impl Actor {
+    /// Create a new [`Actor`] with a given speed.
+    #[must_use]
+    pub fn new(speed: u32) -> Self {
+        Actor { speed, energy: 0 }
+    }
+
+    /// Return if the actor have enough energy to take a turn.
+    #[must_use]
+    pub fn is_ready(&self) -> bool {
+        self.energy >= ACTION_COST
+    }
+}
+
+/// Marker component for an [`Actor`] whose turn it is.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct TakingTurn;
+
+/// Event sent when an [`Actor`] act, ending its turn.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ActionTaken {
+    /// Actor that acted.
+    pub entity: Entity,
+    /// Energy spent by the action.
+    pub cost: u32,
+}
+
+/// System to give the next turn when no [`Actor`] is [`TakingTurn`].
+///
+/// Energy is ticked until at least one actor is ready. Every ready non player actor take its turn
+/// at the same time, the player only take its turn when no other actor is ready.
+pub fn schedule_turns(
+    mut commands: Commands,
+    mut actors: Query<(Entity, &mut Actor, Has<Player>)>,
+    taking_turn: Query<(), With<TakingTurn>>,
+) {
+    if !taking_turn.is_empty() || actors.iter().all(|(_, actor, _)| actor.speed == 0) {
+        return;
+    }
+
+    loop {
+        let mut player = None;
+        let mut any_ready = false;
+
+        for (entity, actor, is_player) in &actors {
+            if !actor.is_ready() {
+                continue;
+            }
+
+            if is_player {
+                player = Some(entity);
+            } else {
+                commands.entity(entity).insert(TakingTurn);
+                any_ready = true;
+            }
+        }
+
+        if any_ready {
+            return;
+        }
+        if let Some(player) = player {
+            commands.entity(player).insert(TakingTurn);
+            return;
+        }
+
+        for (_, mut actor, _) in &mut actors {
+            actor.energy += actor.speed;
+        }
+    }
+}
+
+/// System to spend the energy of every [`ActionTaken`] and end the actor turn.
+pub fn end_turn(
+    mut commands: Commands,
+    mut actions: EventReader<ActionTaken>,
+    mut actors: Query<&mut Actor, With<TakingTurn>>,
+) {
+    for ActionTaken { entity, cost } in actions.read().copied() {
+        let Ok(mut actor) = actors.get_mut(entity) else {
+            continue;
+        };
+
+        actor.energy = actor.energy.saturating_sub(cost);
+        commands.entity(entity).remove::<TakingTurn>();
+    }
+}
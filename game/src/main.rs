@@ -1,7 +1,6 @@
 #![allow(missing_docs)]
 
-use std::time::Duration;
-
+use bevy::asset::io::file::FileAssetReader;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 use engine::prelude::*;
@@ -23,18 +22,83 @@ fn main() {
     app.add_plugins(default_plugin)
         .insert_resource(ClearColor(Color::BLACK))
         .init_asset::<RoomLayout>()
+        .init_asset::<Palette>()
         .init_asset_loader::<RoomLayoutLoader>()
-        .add_systems(Startup, (setup, create_global_atlas, setup_tile_map))
+        .init_asset::<TileSet>()
+        .init_asset_loader::<TileSetLoader>()
+        .add_event::<SetTile>()
+        .init_resource::<GridIndex>()
+        .init_resource::<PlayerDistanceMap>()
+        .insert_resource(MovementOptions { diagonal: true })
+        .add_event::<MoveIntent>()
+        .add_event::<Bump>()
+        .add_event::<ActionTaken>()
+        .init_resource::<InputBindings>()
+        .init_resource::<KeyRepeat>()
+        .init_resource::<GamepadOptions>()
+        .init_resource::<InputBuffer>()
+        .init_resource::<HoveredTile>()
+        .add_event::<ActionEvent>()
+        .add_event::<AnimationFinished>()
+        .init_resource::<TimeScale>()
+        .add_systems(
+            Startup,
+            (
+                setup,
+                create_global_atlas,
+                setup_tile_map,
+                setup_tooltip,
+                load_bindings,
+            ),
+        )
         .add_systems(
             Update,
             (
                 proc_generator,
+                stream_rooms,
                 update_camera,
-                //unload_outside,
+                (update_viewshed, fog_of_war).chain(),
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                (
+                    (update_transform, transform_animation),
+                    tween_animation,
+                    run_follow_ups,
+                )
+                    .chain(),
+                update_grid_index,
+                update_player_distance_map,
             ),
         )
-        .add_systems(Update, (update_transform, transform_animation))
-        .add_systems(PostUpdate, (atlas_to_sprite, input));
+        .add_systems(
+            Update,
+            (
+                pack_sprite_folder,
+                apply_set_tile,
+                (read_actions, buffer_actions).chain(),
+                save_bindings,
+                (hover_tile, (highlight_hovered, update_tooltip)).chain(),
+                click_to_move,
+                bump_effect,
+            ),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                (invalidate_palette_cache, atlas_to_sprite).chain(),
+                (
+                    schedule_turns,
+                    input,
+                    resolve_movement,
+                    end_turn,
+                    face_sprite,
+                )
+                    .chain(),
+            ),
+        );
 
     one_shot_systems
         .0
@@ -42,10 +106,14 @@ fn main() {
 
     app.insert_resource(one_shot_systems);
 
+    if let Some(input_config) = InputConfig::user("cas_game") {
+        app.insert_resource(input_config);
+    }
+
     app.run();
 }
 
-fn setup(mut commands: Commands) {
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.spawn((
         GridTransform::from_xy(WIDTH / 2, HEIGHT / 2),
         Camera2d,
@@ -58,74 +126,100 @@ fn setup(mut commands: Commands) {
     ));
     commands.spawn(Player);
     commands.spawn(Generator(10));
+
+    // pack the individual sprite into the main atlas instead if there are any
+    if FileAssetReader::get_base_path()
+        .join("assets/textures/sprites")
+        .is_dir()
+    {
+        commands.spawn(SpriteFolder::new(
+            "main",
+            asset_server.load_folder("textures/sprites"),
+        ));
+    }
 }
 
-// TODO: Use an input event instead of this
+#[allow(clippy::type_complexity)]
 fn input(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut map: ResMut<Map>,
-    mut transform: Single<&mut GridTransform, With<Player>>,
-    mut animation: Single<&mut TransformAnimation, With<Player>>,
-    mut sprite: Single<&mut AtlasSprite, With<Player>>,
+    mut commands: Commands,
+    mut buffer: ResMut<InputBuffer>,
+    mut bumps: EventReader<Bump>,
+    player: Single<
+        (
+            Entity,
+            &GridTransform,
+            &TransformAnimation,
+            Option<&mut MovePath>,
+            Has<TakingTurn>,
+        ),
+        With<Player>,
+    >,
+    mut intents: EventWriter<MoveIntent>,
+    mut turns: EventWriter<ActionTaken>,
 ) {
-    for i in keyboard_input.get_just_pressed() {
-        let move_dir = match i {
-            KeyCode::KeyW | KeyCode::ArrowUp => Direction::Up,
-            KeyCode::KeyS | KeyCode::ArrowDown => Direction::Down,
-            KeyCode::KeyA | KeyCode::ArrowLeft => Direction::Left,
-            KeyCode::KeyD | KeyCode::ArrowRight => Direction::Right,
-            _ => Direction::Zero,
-        };
-        if !move_dir.is_zero() && animation.duration.is_zero() {
-            animation.old_transform = **transform;
-
-            let headed_position = transform.translate(move_dir, 1).translation;
-
-            let Some(tile_map) = map.curr_room() else {
-                return;
-            };
-
-            let mut just_door = false;
-
-            match tile_map.get_tile(
-                headed_position
-                    .rem_euclid(IVec2::new(WIDTH.into(), HEIGHT.into()))
-                    .as_uvec2(),
-            ) {
-                TileType::Ground => transform.translate_mut(move_dir, 1),
-                TileType::Door(dir) => {
-                    match dir {
-                        CompassDir::North => map.curr_room_pos.1 -= 1,
-                        CompassDir::East => map.curr_room_pos.0 += 1,
-                        CompassDir::South => map.curr_room_pos.1 += 1,
-                        CompassDir::West => map.curr_room_pos.0 -= 1,
-                        _ => unreachable!(),
-                    }
-                    transform.translate_mut(move_dir, 3);
-                    just_door = true;
-                }
-                _ => (),
-            }
+    let (entity, transform, animation, path, taking_turn) = player.into_inner();
+
+    // stop following the path once it is blocked or the player take over
+    let bumped = bumps.read().any(|b| b.entity == entity);
+    if path.is_some() && (bumped || !buffer.is_empty()) {
+        commands.entity(entity).remove::<MovePath>();
+    }
 
-            if matches!(move_dir, Direction::Left) {
-                sprite.flip_y = true;
-            } else if matches!(move_dir, Direction::Right) {
-                sprite.flip_y = false;
+    if !taking_turn || !animation.duration.is_zero() {
+        return;
+    }
+
+    while let Some(action) = buffer.pop() {
+        match action {
+            Action::Move(direction) if !direction.is_zero() => {
+                intents.send(MoveIntent { entity, direction });
             }
+            Action::Wait => {
+                turns.send(ActionTaken {
+                    entity,
+                    cost: ACTION_COST,
+                });
+            }
+            // only action taking a turn are buffered, this skip moving nowhere
+            _ => continue,
+        }
 
-            animation.duration = Duration::from_millis(if just_door { 200 } else { 100 });
-            break;
+        return;
+    }
+
+    if bumped {
+        return;
+    }
+    if let Some(mut path) = path {
+        match path.next_direction(transform.translation) {
+            Some(direction) => {
+                intents.send(MoveIntent { entity, direction });
+            }
+            None => {
+                commands.entity(entity).remove::<MovePath>();
+            }
         }
     }
 }
 
+fn bump_effect(mut commands: Commands, mut bumps: EventReader<Bump>) {
+    for Bump {
+        entity, direction, ..
+    } in bumps.read()
+    {
+        commands
+            .entity(*entity)
+            .insert(Animator::new(Tweenable::bump(*direction)));
+    }
+}
+
 fn update_camera(mut camera: Single<&mut GridTransform, With<Camera>>, map: Res<Map>) {
     if !map.is_changed() {
         return;
     }
 
-    camera.translation = IVec2::new(
-        map.curr_room_pos.0 * WIDTH as i32 + WIDTH as i32 / 2,
-        map.curr_room_pos.1 * HEIGHT as i32 + HEIGHT as i32 / 2,
+    camera.translation = Map::room_to_world(
+        map.curr_room_pos,
+        UVec2::new((WIDTH / 2).into(), (HEIGHT / 2).into()),
     );
 }